@@ -32,6 +32,7 @@ use manta_api::*;
 use manta_asset::*;
 use manta_crypto::*;
 use manta_data::*;
+use pallet_manta_pay::{batch_verify, precomputed_coins};
 use rand_chacha::ChaCha20Rng;
 use std::{fs::File, io::prelude::*};
 
@@ -42,6 +43,8 @@ criterion_group!(
 	bench_pedersen_com,
 	bench_merkle_tree,
 	bench_transfer_verify,
+	bench_transfer_batch_verify,
+	bench_prepared_key,
 	bench_reclaim_verify,
 	bench_transfer_prove,
 	bench_reclaim_prove
@@ -162,6 +165,173 @@ fn bench_transfer_verify(c: &mut Criterion) {
 	bench_group.finish();
 }
 
+fn bench_transfer_batch_verify(c: &mut Criterion) {
+	let hash_param_seed = HASH_PARAM_SEED;
+	let commit_param_seed = COMMIT_PARAM_SEED;
+
+	let mut rng = ChaCha20Rng::from_seed(commit_param_seed);
+	let commit_param = CommitmentScheme::setup(&mut rng).unwrap();
+
+	let mut rng = ChaCha20Rng::from_seed(hash_param_seed);
+	let hash_param = Hash::setup(&mut rng).unwrap();
+
+	let mut file = File::open("transfer_pk.bin").unwrap();
+	let mut transfer_key_bytes: Vec<u8> = vec![];
+	file.read_to_end(&mut transfer_key_bytes).unwrap();
+	let buf: &[u8] = transfer_key_bytes.as_ref();
+	let pk = Groth16Pk::deserialize_unchecked(buf).unwrap();
+
+	println!("proving key loaded from disk");
+
+	let batch_size = pallet_manta_pay::MAX_BATCH_SIZE;
+	let mut batch = Vec::with_capacity(batch_size);
+	let mut batch_clone = Vec::with_capacity(batch_size);
+	let mut sk = [0u8; 32];
+	for _ in 0..batch_size {
+		// sender
+		rng.fill_bytes(&mut sk);
+		let sender_1 = MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &100).unwrap();
+
+		rng.fill_bytes(&mut sk);
+		let sender_2 = MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &300).unwrap();
+
+		let list = vec![sender_1.utxo, sender_2.utxo];
+		let sender_1 = sender_1.build(&hash_param, &list).unwrap();
+		let sender_2 = sender_2.build(&hash_param, &list).unwrap();
+
+		// receiver
+		rng.fill_bytes(&mut sk);
+		let receiver_1_full =
+			MantaAssetFullReceiver::sample(&commit_param, &sk, &TEST_ASSET, &()).unwrap();
+		let receiver_1 = receiver_1_full
+			.shielded_address
+			.process(&150, &mut rng)
+			.unwrap();
+
+		rng.fill_bytes(&mut sk);
+		let receiver_2_full =
+			MantaAssetFullReceiver::sample(&commit_param, &sk, &TEST_ASSET, &()).unwrap();
+		let receiver_2 = receiver_2_full
+			.shielded_address
+			.process(&250, &mut rng)
+			.unwrap();
+
+		// form the transaction payload
+		let transfer_data = generate_private_transfer_payload(
+			commit_param.clone(),
+			hash_param.clone(),
+			&pk,
+			sender_1,
+			sender_2,
+			receiver_1,
+			receiver_2,
+			&mut rng,
+		)
+		.unwrap();
+		batch.push(PrivateTransferData::deserialize(transfer_data.as_ref()).unwrap());
+		batch_clone.push(PrivateTransferData::deserialize(transfer_data.as_ref()).unwrap());
+	}
+
+	println!("start benchmarking batch proof verification");
+	let mut bench_group = c.benchmark_group("private transfer");
+
+	let bench_str = format!("ZKP verification, {} proofs one by one", batch_size);
+	bench_group.bench_function(bench_str, move |b| {
+		b.iter(|| assert!(batch.iter().all(|data| data.verify(&TRANSFER_PK))))
+	});
+
+	let transfer_key = batch_verify::prepare_key(&TRANSFER_PK).unwrap();
+	let bench_str = format!("ZKP verification, {} proofs batched", batch_size);
+	bench_group.bench_function(bench_str, move |b| {
+		b.iter(|| {
+			assert!(batch_verify::batch_verify_transfers(
+				&transfer_key,
+				&batch_clone
+			))
+		})
+	});
+
+	bench_group.finish();
+}
+
+fn bench_prepared_key(c: &mut Criterion) {
+	use ark_bls12_381::Bls12_381;
+	use ark_groth16::{prepare_verifying_key, Proof, VerifyingKey};
+	use codec::{Decode, Encode};
+
+	// the prepared keys as they are stored in the ledger, with their checksums
+	let transfer_entry = (
+		TRANSFER_PK.get_checksum().unwrap(),
+		batch_verify::prepare_key(&TRANSFER_PK).unwrap(),
+	)
+		.encode();
+	let reclaim_entry = (
+		RECLAIM_PK.get_checksum().unwrap(),
+		batch_verify::prepare_key(&RECLAIM_PK).unwrap(),
+	)
+		.encode();
+	println!(
+		"stored prepared keys: transfer {} bytes, reclaim {} bytes",
+		transfer_entry.len(),
+		reclaim_entry.len()
+	);
+
+	let mut bench_group = c.benchmark_group("prepared verifying key");
+
+	// what every private transfer pays: decode the stored key, then verify
+	let transfer_data =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	let bench_str = "transfer verification, decoding the stored key".to_string();
+	bench_group.bench_function(bench_str, move |b| {
+		b.iter(|| {
+			let (_, key) = <([u8; 32], Vec<u8>)>::decode(&mut transfer_entry.as_slice()).unwrap();
+			assert!(batch_verify::verify_transfer(&key, &transfer_data))
+		})
+	});
+
+	// what a private transfer would pay without the stored key: the key is trusted
+	// through its checksum, so it is deserialized unchecked, then prepared
+	let transfer_data =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	let bench_str = "transfer verification, preparing the key".to_string();
+	bench_group.bench_function(bench_str, move |b| {
+		b.iter(|| {
+			let vk = VerifyingKey::<Bls12_381>::deserialize_unchecked(TRANSFER_PK.data).unwrap();
+			let pvk = prepare_verifying_key(&vk);
+			let proof = Proof::<Bls12_381>::deserialize(transfer_data.proof.as_ref()).unwrap();
+			let inputs = batch_verify::transfer_public_inputs(&transfer_data).unwrap();
+			assert!(batch_verify::batch_verify(&pvk, &[(proof, inputs)]))
+		})
+	});
+
+	let reclaim_data =
+		ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+	let bench_str = "reclaim verification, decoding the stored key".to_string();
+	bench_group.bench_function(bench_str, move |b| {
+		b.iter(|| {
+			let (_, key) = <([u8; 32], Vec<u8>)>::decode(&mut reclaim_entry.as_slice()).unwrap();
+			assert!(batch_verify::verify_reclaim(&key, &reclaim_data))
+		})
+	});
+
+	let reclaim_data =
+		ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+	let bench_str = "reclaim verification, preparing the key".to_string();
+	bench_group.bench_function(bench_str, move |b| {
+		b.iter(|| {
+			let vk = VerifyingKey::<Bls12_381>::deserialize_unchecked(RECLAIM_PK.data).unwrap();
+			let pvk = prepare_verifying_key(&vk);
+			let proof = Proof::<Bls12_381>::deserialize(reclaim_data.proof.as_ref()).unwrap();
+			let inputs = batch_verify::reclaim_public_inputs(&reclaim_data).unwrap();
+			assert!(batch_verify::batch_verify(&pvk, &[(proof, inputs)]))
+		})
+	});
+
+	bench_group.finish();
+}
+
 fn bench_merkle_tree(c: &mut Criterion) {
 	let hash_param_seed = HASH_PARAM_SEED;
 	let mut rng = ChaCha20Rng::from_seed(hash_param_seed);
@@ -0,0 +1,111 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Manta pay host functions
+//!
+//! Host functions that run the most expensive cryptographic primitives of manta pay
+//! at native speed, instead of inside the Wasm runtime:
+//!
+//! * `pairing_check` - Check that a product of BLS12-381 pairings is one.
+//! This is the final step of every Groth16 verification.
+//! * `ledger_update` - Add a coin commitment to its shard of the coin ledger and update the root of the shard.
+//! Only the shard crosses the host boundary, not the whole ledger.
+//! The shard Merkle tree is hashed with the Pedersen hash over Jubjub under the ledger hash parameters
+//! of the node, which must match the checksum of the parameters of the runtime.
+//!
+//! The node has to register the functions with its executor, e.g.,
+//! `type ExtendHostFunctions = manta_pay_host::manta_crypto::HostFunctions;`.
+//! The pallet calls them when it is built with the `native-crypto` feature.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use sp_runtime_interface::runtime_interface;
+use sp_std::vec::Vec;
+
+#[runtime_interface]
+pub trait MantaCrypto {
+	/// Check that the product of the pairings of `pairs` is one.
+	/// Each pair is a compressed BLS12-381 G1 point and a compressed G2 point.
+	/// Returns `false` if any of the points cannot be deserialized.
+	fn pairing_check(pairs: Vec<(Vec<u8>, Vec<u8>)>) -> bool {
+		native::pairing_check(&pairs)
+	}
+
+	/// Add the coin commitment `cm` to the SCALE encoded shard `shard`, i.e., the shard
+	/// `cm[0]` of the coin ledger, and return the encoded shard with the updated root.
+	/// Returns `None` if the hash parameters of the node do not have the checksum
+	/// `param_checksum`, or if the shard cannot be decoded or updated.
+	fn ledger_update(shard: Vec<u8>, cm: [u8; 32], param_checksum: [u8; 32]) -> Option<Vec<u8>> {
+		native::ledger_update(&shard, &cm, &param_checksum)
+	}
+}
+
+/// The native implementations of the host functions.
+#[cfg(feature = "std")]
+pub mod native {
+	use ark_bls12_381::{Bls12_381, G1Affine, G2Affine};
+	use ark_ec::PairingEngine;
+	use ark_ff::One;
+	use ark_serialize::CanonicalDeserialize;
+	use codec::{Decode, Encode};
+	use manta_crypto::*;
+	use manta_ledger::{LedgerSharding, MantaPrivateAssetLedger};
+	use once_cell::sync::Lazy;
+
+	/// The hash parameters are only deserialized once per process.
+	static HASH_PARAM_DES: Lazy<Option<HashParam>> =
+		Lazy::new(|| HashParam::deserialize(HASH_PARAM.data).ok());
+
+	/// The checksum of the hash parameters, computed once per process.
+	static HASH_PARAM_CHECKSUM: Lazy<Option<[u8; 32]>> =
+		Lazy::new(|| HASH_PARAM.get_checksum().ok());
+
+	/// Check that the product of the pairings of `pairs` is one.
+	pub fn pairing_check(pairs: &[(Vec<u8>, Vec<u8>)]) -> bool {
+		let mut prepared = Vec::with_capacity(pairs.len());
+		for (g1, g2) in pairs {
+			let g1 = match G1Affine::deserialize(g1.as_ref()) {
+				Ok(g1) => g1,
+				Err(_) => return false,
+			};
+			let g2 = match G2Affine::deserialize(g2.as_ref()) {
+				Ok(g2) => g2,
+				Err(_) => return false,
+			};
+			prepared.push((g1.into(), g2.into()));
+		}
+		Bls12_381::product_of_pairings(&prepared).is_one()
+	}
+
+	/// Add `cm` to the encoded shard `shard` of the coin ledger, under the ledger hash parameters,
+	/// if they have the checksum `param_checksum`.
+	/// The shard is updated in an otherwise empty ledger: the update only reads the shard of `cm`.
+	pub fn ledger_update(
+		shard: &[u8],
+		cm: &[u8; 32],
+		param_checksum: &[u8; 32],
+	) -> Option<Vec<u8>> {
+		if HASH_PARAM_CHECKSUM.as_ref()? != param_checksum {
+			return None;
+		}
+		let hash_param = HASH_PARAM_DES.as_ref()?;
+		let shard_index = cm[0] as usize;
+		let mut ledger = MantaPrivateAssetLedger::default();
+		ledger.shard[shard_index] = Decode::decode(&mut &shard[..]).ok()?;
+		ledger.update(cm, hash_param.clone()).ok()?;
+		Some(ledger.shard[shard_index].encode())
+	}
+}
@@ -0,0 +1,289 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Verification of the Groth16 proofs of private transfers and reclaims.
+//!
+//! A Groth16 proof `(A, B, C)` with public inputs `x` is valid if
+//! `e(A, B) = e(alpha, beta) * e(L(x), gamma) * e(C, delta)`.
+//! Given `n` proofs and random scalars `r_i`, the pallet checks
+//! `prod e(r_i A_i, B_i) * e(-sum r_i L(x_i), gamma) * e(-sum r_i C_i, delta)
+//! = e(alpha, beta)^(sum r_i)`,
+//! which costs a single final exponentiation and `n + 2` Miller loops
+//! instead of `n` final exponentiations and `3n` Miller loops.
+//! The scalars are derived from a hash of all the proofs and their public inputs,
+//! so they are fixed only after the whole batch is.
+//! A single proof is checked with `r = 1`.
+//!
+//! All the verifications take the verifying key prepared by `prepare_key`, which stores
+//! `e(alpha, beta)` next to the key so that its final exponentiation is computed once per key.
+//! The line coefficients of `-gamma` and `-delta` are about 40 KB, so they are not stored:
+//! `load_key` prepares them in memory, which keeps the key read by every call small.
+//! With the `native-crypto` feature, the pairings run on the host instead.
+
+#[cfg(feature = "native-crypto")]
+use crate::primitives::pairing_check;
+use ark_bls12_381::{Bls12_381, Fq12, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{One, PrimeField, ToConstraintField, Zero};
+use ark_groth16::{prepare_verifying_key, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+use manta_crypto::*;
+use manta_data::*;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+/// Prepare the verification key `vk`: serialize it, followed by `e(alpha, beta)`,
+/// without compression so that it is cheap to load from the ledger.
+pub fn prepare_key(vk: &VerificationKey) -> Option<Vec<u8>> {
+	let vk = VerifyingKey::<Bls12_381>::deserialize(vk.data).ok()?;
+	let pvk = prepare_verifying_key(&vk);
+	let mut bytes = Vec::new();
+	pvk.vk.serialize_unchecked(&mut bytes).ok()?;
+	pvk.alpha_g1_beta_g2.serialize_unchecked(&mut bytes).ok()?;
+	Some(bytes)
+}
+
+/// Load a key serialized by `prepare_key`, and prepare the line coefficients
+/// of `-gamma` and `-delta`. The key comes from the ledger, so it is not checked again.
+fn load_key(mut prepared_key: &[u8]) -> Option<PreparedVerifyingKey<Bls12_381>> {
+	let vk = VerifyingKey::<Bls12_381>::deserialize_unchecked(&mut prepared_key).ok()?;
+	let alpha_g1_beta_g2 = Fq12::deserialize_unchecked(&mut prepared_key).ok()?;
+	Some(PreparedVerifyingKey {
+		gamma_g2_neg_pc: (-vk.gamma_g2).into(),
+		delta_g2_neg_pc: (-vk.delta_g2).into(),
+		alpha_g1_beta_g2,
+		vk,
+	})
+}
+
+/// Verify the proof of a single private transfer.
+pub fn verify_transfer(prepared_key: &[u8], data: &PrivateTransferData) -> bool {
+	batch_verify_transfers(prepared_key, ark_std::slice::from_ref(data))
+}
+
+/// Verify the proof of a single reclaim.
+pub fn verify_reclaim(prepared_key: &[u8], data: &ReclaimData) -> bool {
+	let pvk = match load_key(prepared_key) {
+		Some(pvk) => pvk,
+		None => return false,
+	};
+	let proof = match Proof::<Bls12_381>::deserialize(data.proof.as_ref()) {
+		Ok(proof) => proof,
+		Err(_) => return false,
+	};
+	match reclaim_public_inputs(data) {
+		Some(inputs) => batch_verify(&pvk, &[(proof, inputs)]),
+		None => false,
+	}
+}
+
+/// Verify the proofs of all the private transfers in `data` at once.
+/// Returns `true` only if every proof is valid.
+pub fn batch_verify_transfers(prepared_key: &[u8], data: &[PrivateTransferData]) -> bool {
+	let pvk = match load_key(prepared_key) {
+		Some(pvk) => pvk,
+		None => return false,
+	};
+
+	let mut instances = Vec::with_capacity(data.len());
+	for transfer in data {
+		let proof = match Proof::<Bls12_381>::deserialize(transfer.proof.as_ref()) {
+			Ok(proof) => proof,
+			Err(_) => return false,
+		};
+		let inputs = match transfer_public_inputs(transfer) {
+			Some(inputs) => inputs,
+			None => return false,
+		};
+		instances.push((proof, inputs));
+	}
+
+	batch_verify(&pvk, &instances)
+}
+
+/// Verify the Groth16 `instances`, each a proof with its public inputs,
+/// against `pvk` with a random linear combination of the pairing equations.
+pub fn batch_verify(
+	pvk: &PreparedVerifyingKey<Bls12_381>,
+	instances: &[(Proof<Bls12_381>, Vec<Fr>)],
+) -> bool {
+	if instances.is_empty() {
+		return true;
+	}
+
+	let seed = if instances.len() > 1 {
+		match transcript(instances) {
+			Some(seed) => Some(seed),
+			None => return false,
+		}
+	} else {
+		None
+	};
+
+	let vk = &pvk.vk;
+	let mut pairs: Vec<(G1Affine, G2Affine)> = Vec::with_capacity(instances.len());
+	let mut sum_r = Fr::zero();
+	let mut sum_l = G1Projective::zero();
+	let mut sum_c = G1Projective::zero();
+
+	for (i, (proof, inputs)) in instances.iter().enumerate() {
+		if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+			return false;
+		}
+		let r = match &seed {
+			Some(seed) => challenge(seed, i as u64),
+			None => Fr::one(),
+		};
+
+		// L(x) = gamma_abc[0] + sum x_j * gamma_abc[j + 1]
+		let mut l = vk.gamma_abc_g1[0].into_projective();
+		for (x, base) in inputs.iter().zip(vk.gamma_abc_g1.iter().skip(1)) {
+			l += &base.mul(x.into_repr());
+		}
+
+		if r.is_one() {
+			sum_l += &l;
+			sum_c.add_assign_mixed(&proof.c);
+			pairs.push((proof.a, proof.b));
+		} else {
+			sum_l += &l.mul(r.into_repr());
+			sum_c += &proof.c.mul(r.into_repr());
+			pairs.push((proof.a.mul(r.into_repr()).into_affine(), proof.b));
+		}
+		sum_r += &r;
+	}
+
+	check_combination(pvk, pairs, sum_l, sum_c, sum_r)
+}
+
+/// Check `prod e(A, B) * e(-sum_l, gamma) * e(-sum_c, delta) = e(alpha, beta)^sum_r`
+/// on the host.
+#[cfg(feature = "native-crypto")]
+fn check_combination(
+	pvk: &PreparedVerifyingKey<Bls12_381>,
+	mut pairs: Vec<(G1Affine, G2Affine)>,
+	sum_l: G1Projective,
+	sum_c: G1Projective,
+	sum_r: Fr,
+) -> bool {
+	let vk = &pvk.vk;
+	pairs.push(((-sum_l).into_affine(), vk.gamma_g2));
+	pairs.push(((-sum_c).into_affine(), vk.delta_g2));
+	pairs.push((
+		(-vk.alpha_g1.mul(sum_r.into_repr())).into_affine(),
+		vk.beta_g2,
+	));
+	pairing_check(&pairs)
+}
+
+/// Check `prod e(A, B) * e(-sum_l, gamma) * e(-sum_c, delta) = e(alpha, beta)^sum_r`
+/// inside the runtime, with the prepared parts of `pvk`.
+#[cfg(not(feature = "native-crypto"))]
+fn check_combination(
+	pvk: &PreparedVerifyingKey<Bls12_381>,
+	pairs: Vec<(G1Affine, G2Affine)>,
+	sum_l: G1Projective,
+	sum_c: G1Projective,
+	sum_r: Fr,
+) -> bool {
+	use ark_ec::PairingEngine;
+	use ark_ff::Field;
+
+	let mut prepared: Vec<(
+		<Bls12_381 as PairingEngine>::G1Prepared,
+		<Bls12_381 as PairingEngine>::G2Prepared,
+	)> = pairs
+		.into_iter()
+		.map(|(g1, g2)| (g1.into(), g2.into()))
+		.collect();
+	prepared.push((sum_l.into_affine().into(), pvk.gamma_g2_neg_pc.clone()));
+	prepared.push((sum_c.into_affine().into(), pvk.delta_g2_neg_pc.clone()));
+
+	let miller_loop = Bls12_381::miller_loop(&prepared);
+	match Bls12_381::final_exponentiation(&miller_loop) {
+		Some(res) => res == pvk.alpha_g1_beta_g2.pow(sum_r.into_repr()),
+		None => false,
+	}
+}
+
+/// The public inputs of the transfer circuit, in the order the circuit allocates them:
+/// the sender and receiver commitments, the void numbers, then the ledger roots.
+pub fn transfer_public_inputs(data: &PrivateTransferData) -> Option<Vec<Fr>> {
+	let k_old_1 = CommitmentOutput::deserialize(data.sender_1.k.as_ref()).ok()?;
+	let k_old_2 = CommitmentOutput::deserialize(data.sender_2.k.as_ref()).ok()?;
+	let cm_new_1 = CommitmentOutput::deserialize(data.receiver_1.cm.as_ref()).ok()?;
+	let cm_new_2 = CommitmentOutput::deserialize(data.receiver_2.cm.as_ref()).ok()?;
+
+	let mut inputs = [
+		k_old_1.x, k_old_1.y, k_old_2.x, k_old_2.y, cm_new_1.x, cm_new_1.y, cm_new_2.x, cm_new_2.y,
+	]
+	.to_vec();
+	for bytes in [
+		data.sender_1.void_number,
+		data.sender_2.void_number,
+		data.sender_1.root,
+		data.sender_2.root,
+	]
+	.iter()
+	{
+		inputs.extend(ToConstraintField::<Fr>::to_field_elements(bytes.as_ref())?);
+	}
+	Some(inputs)
+}
+
+/// The public inputs of the reclaim circuit, in the order the circuit allocates them:
+/// the sender and receiver commitments, the void numbers, the ledger roots,
+/// then the reclaimed amount and the asset id.
+pub fn reclaim_public_inputs(data: &ReclaimData) -> Option<Vec<Fr>> {
+	let k_old_1 = CommitmentOutput::deserialize(data.sender_1.k.as_ref()).ok()?;
+	let k_old_2 = CommitmentOutput::deserialize(data.sender_2.k.as_ref()).ok()?;
+	let cm_new = CommitmentOutput::deserialize(data.receiver.cm.as_ref()).ok()?;
+
+	let mut inputs = [
+		k_old_1.x, k_old_1.y, k_old_2.x, k_old_2.y, cm_new.x, cm_new.y,
+	]
+	.to_vec();
+	for bytes in [
+		data.sender_1.void_number,
+		data.sender_2.void_number,
+		data.sender_1.root,
+		data.sender_2.root,
+	]
+	.iter()
+	{
+		inputs.extend(ToConstraintField::<Fr>::to_field_elements(bytes.as_ref())?);
+	}
+	inputs.push(Fr::from(data.reclaim_amount));
+	inputs.push(Fr::from(data.asset_id as u64));
+	Some(inputs)
+}
+
+/// Hash all the proofs and public inputs of the batch.
+fn transcript(instances: &[(Proof<Bls12_381>, Vec<Fr>)]) -> Option<Vec<u8>> {
+	let mut bytes = b"manta-pay-batch-verify".to_vec();
+	for (proof, inputs) in instances {
+		proof.serialize(&mut bytes).ok()?;
+		inputs.serialize(&mut bytes).ok()?;
+	}
+	Some(BlakeTwo256::hash(&bytes).as_ref().to_vec())
+}
+
+/// Derive the `index`-th scalar of the random linear combination from `seed`.
+fn challenge(seed: &[u8], index: u64) -> Fr {
+	let mut bytes = seed.to_vec();
+	bytes.extend_from_slice(&index.to_le_bytes());
+	Fr::from_le_bytes_mod_order(BlakeTwo256::hash(&bytes).as_ref())
+}
@@ -0,0 +1,103 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The hash of the shard Merkle trees of the coin ledger.
+//!
+//! A runtime picks one with `Config::LedgerHash`.
+//! The roots of the shards are public inputs of the transfer and reclaim circuits,
+//! so the hash must match the one the circuits of the `Config::Verifier` are built with.
+//! `Pedersen` matches the circuits of `manta-api`, and is the only implementation.
+//!
+//! Poseidon is not implemented. A Poseidon ledger would have roots that no circuit of
+//! `manta-api` can prove against, so it is blocked until `manta-api` ships transfer and
+//! reclaim circuits, parameters and payloads built with Poseidon.
+
+use crate::primitives;
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use manta_crypto::*;
+use manta_ledger::MantaPrivateAssetLedger;
+
+/// A hash for the shard Merkle trees of the coin ledger.
+pub trait LedgerHash {
+	/// The parameters of the hash.
+	type Param: Clone;
+
+	/// The checksum of the parameters, stored in the ledger when it is initialized,
+	/// or `None` if it cannot be computed.
+	fn param_checksum() -> Option<[u8; 32]>;
+
+	/// The parameters of the hash, or `None` if they cannot be loaded.
+	fn param() -> Option<Self::Param>;
+
+	/// Add `cm` to its shard of `ledger`, and update the root of the shard.
+	/// Returns `false` if the shard could not be updated.
+	fn update(ledger: &mut MantaPrivateAssetLedger, cm: &[u8; 32], param: &Self::Param) -> bool;
+
+	/// The serialized authentication path from the leaf at `index` of `leaves`
+	/// to the root of the tree of `leaves`.
+	fn witness_path(param: &Self::Param, leaves: &[[u8; 32]], index: usize) -> Option<Vec<u8>>;
+}
+
+/// Pedersen hash over Jubjub, with the parameters of `manta_crypto`.
+pub struct Pedersen;
+
+impl LedgerHash for Pedersen {
+	type Param = HashParam;
+
+	fn param_checksum() -> Option<[u8; 32]> {
+		HASH_PARAM
+			.get_checksum()
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to compute the hash param checksum with error: {:?}", e);
+			})
+			.ok()
+	}
+
+	fn param() -> Option<HashParam> {
+		HashParam::deserialize(HASH_PARAM.data)
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to deserialize the hash param with error: {:?}", e);
+			})
+			.ok()
+	}
+
+	fn update(ledger: &mut MantaPrivateAssetLedger, cm: &[u8; 32], param: &HashParam) -> bool {
+		primitives::ledger_update(ledger, cm, param)
+	}
+
+	fn witness_path(param: &HashParam, leaves: &[[u8; 32]], index: usize) -> Option<Vec<u8>> {
+		let tree = LedgerMerkleTree::new(param.clone(), leaves)
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to build the coin witness with error: {:?}", e);
+			})
+			.ok()?;
+		let path = tree
+			.generate_proof(index, &leaves[index])
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to build the coin witness with error: {:?}", e);
+			})
+			.ok()?;
+
+		let mut path_bytes = Vec::new();
+		path.serialize(&mut path_bytes)
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to build the coin witness with error: {:?}", e);
+			})
+			.ok()?;
+		Some(path_bytes)
+	}
+}
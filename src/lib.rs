@@ -52,22 +52,61 @@
 //! * Converting public assets to private assets, and vice versa.
 //! * Move private assets between accounts (in UTXO model).
 //!
+//! Private assets of two different assets cannot be swapped in one transaction. A trustless swap
+//! needs a circuit that proves both legs together, with the value of each asset preserved, and
+//! `manta-api` has no such circuit yet.
+//!
 //! ## Interface
 //!
 //! ### Dispatchable Functions
 //!
-//! * `init_asset` - Issues the total supply of a new fungible asset to the account of the caller of the function.
+//! * `init_asset` - Issues the total supply of a new fungible asset to the account of the `CreateOrigin`.
+//! The first call also initializes the shielded ledger shared by all the assets.
+//! * `create_asset` - Issues the total supply of a new fungible asset under the next free asset id,
+//! reserving a deposit from the creator.
 //! * `transfer_asset` - Transfers an `amount` of units of fungible asset `id` from the balance of
 //! the function caller's account (`origin`) to a `target` account.
 //! * `mint_private_asset` - Converting an `amount` of units of fungible asset `id` from the caller to a private UTXO.
 //! (The caller does not need to be the owner of this UTXO)
 //! * `private_transfer` - Transfer two input UTXOs into two output UTXOs. Require that 1) the input UTXOs are
 //! already in the ledger and are not spend before 2) the sum of private assets in input UTXOs matches that
-//! of the output UTXOs. The requirements are guaranteed via ZK proof.
+//! of the output UTXOs. The requirements are guaranteed via ZK proof. The transfer circuit of `manta-api`
+//! has no asset id input, so a private transfer is not bound to an asset on chain. Binding it is blocked
+//! on an asset id public input in that circuit.
+//! * `batch_private_transfer` - Apply up to `MAX_BATCH_SIZE` private transfers at once. All the proofs
+//! are checked with a single batched verification. The batch benchmark only has one precomputed transfer so
+//! far, so each transfer is charged the weight of a `private_transfer`.
 //! * `reclaim` - Transfer two input UTXOs into one output UTXOs, and convert the remaining assets to the
 //! public assets. Require that 1) the input UTXOs are already in the ledger and are not spend before; 2) the
 //! sum of private assets in input UTXOs matches that of the output UTXO + the reclaimed amount. The
 //! requirements are guaranteed via ZK proof.
+//! * `pause` / `unpause` - Pause or unpause `transfer_asset`, `mint_private_asset`, `private_transfer`
+//! or `reclaim`, for a single asset or for all the assets.
+//! * `destroy_asset` - Remove an asset with an empty pool and all its balances, in batches of accounts,
+//! and refund the creation deposit. Only the owner of the asset can destroy it.
+//! * `set_min_balance` - Set the minimum balance of an asset and the policy for the dust below it.
+//! * `set_pool_cap` - Set the maximum pool balance of an asset.
+//! * `set_reclaim_limit` - Set the maximum amount of an asset that can be reclaimed per rolling window of blocks.
+//! * `set_auditor_key` - Register the auditor key of an asset. Every minted and reclaimed note of this
+//! asset is then also encrypted to the auditor key. Private transfers do not reveal their asset, so
+//! their auditor notes are optional.
+//!
+//! The proofs are checked by the proof system set as `Config::Verifier`; `Groth16` verifies the
+//! payloads built by `manta-api`.
+//!
+//! The coin ledger is a set of shard Merkle trees hashed with `Config::LedgerHash`; `Pedersen`
+//! matches the circuits of `manta-api`, and is the only hash implemented. There is no Poseidon
+//! hash yet: it is blocked on `manta-api` shipping Poseidon circuits, parameters and payloads.
+//!
+//! Before a proof is verified, every curve point of the payload is checked to be canonically
+//! encoded and in the prime order subgroup, so that a coin cannot be stored under two encodings.
+//!
+//! With the `native-crypto` feature, the pairing checks of the proof verifications and the Pedersen
+//! hashing of the coin ledger updates run at native speed through the host functions of
+//! `manta-pay-host`, which the node must then provide.
+//!
+//! All dispatchable functions are transactional: a call that fails at any step leaves the storage
+//! unchanged, and events are only deposited once all the writes have succeeded.
 //!
 //! Please refer to the [`Call`](./enum.Call.html) enum and its associated variants for documentation on each
 //! function.
@@ -78,6 +117,12 @@
 //! * `balance` - Get the asset balance of `who`.
 //! * `total_supply` - Get the total supply of an asset `id`.
 //! * `pool_balance` - Get the total number of private asset.
+//! * `public_supply` - Get the total number of public asset, i.e., `total_supply` minus `pool_balance`.
+//! * `is_initialized` - Check if an asset `id` has been issued.
+//! * `void_number_count` - Get the number of void numbers revealed so far.
+//! * `coin_count` - Get the number of coins in the ledger.
+//! * `note_count` - Get the number of encrypted notes in the ledger.
+//! * `coin_witness` - Get the Merkle membership witness of a coin commitment.
 //!
 //! Please refer to the [`Module`](./struct.Module.html) struct for details on publicly available functions.
 //!
@@ -113,31 +158,193 @@ mod test;
 extern crate std;
 
 pub use manta_crypto::MantaSerDes;
+pub mod batch_verify;
 pub mod weights;
 pub use weights::WeightInfo;
+pub mod ledger_hash;
+pub mod migrations;
+pub mod payload_check;
 pub mod precomputed_coins;
+pub use ledger_hash::{LedgerHash, Pedersen};
+pub use migrations::Releases;
+pub mod primitives;
+pub mod verifier;
+pub use verifier::{Groth16, Verifier};
+pub mod runtime_api;
+#[cfg(feature = "std")]
+pub mod wallet;
 
 use ark_std::vec::Vec;
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure};
-use frame_system::ensure_signed;
+use codec::{Decode, Encode};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, ensure,
+	storage::IterableStorageDoubleMap,
+	traits::{Currency, EnsureOrigin, Get, ReservableCurrency},
+	transactional,
+	weights::Weight,
+};
+use frame_system::{ensure_root, ensure_signed};
 use manta_asset::{AssetBalance, AssetId, MantaRandomValue, SanityCheck};
 use manta_crypto::*;
 use manta_data::*;
 use manta_ledger::{LedgerSharding, MantaPrivateAssetLedger};
 use sp_runtime::{
-	traits::{StaticLookup, Zero},
-	DispatchError,
+	traits::{BlakeTwo256, Hash as HashT, Saturating, StaticLookup, Zero},
+	DispatchError, RuntimeDebug,
 };
 use sp_std::prelude::*;
 
 /// An abstract struct for manta-pay.
 pub struct MantaPay;
 
+/// A view tag of an encrypted note.
+/// It is derived from the ECIES shared secret of the note, so that a wallet
+/// can skip most of the notes that are not addressed to it without running
+/// the full decryption.
+/// The sender recovers the tag with `wallet::ViewTagRng` and passes it next to the payload.
+/// The tag is not part of the payload, so the proof does not bind it: a wrong tag only
+/// makes the wallet of the receiver skip the note.
+pub type ViewTag = u8;
+
+/// Derive the view tag from the ECIES shared secret of a note.
+/// The receiver computes the secret from its ECIES secret key and the ephemeral key of the note,
+/// the sender from the ephemeral secret key and the ECIES public key of the receiver.
+pub fn derive_view_tag(shared_secret: &[u8; 32]) -> ViewTag {
+	let mut input = b"manta-pay-view-tag".to_vec();
+	input.extend_from_slice(shared_secret);
+	<BlakeTwo256 as HashT>::hash(&input)[0]
+}
+
+/// The maximum size, in bytes, of an encrypted memo.
+pub const MAX_MEMO_SIZE: usize = 512;
+
+/// The maximum number of private transfers in a `batch_private_transfer` call.
+pub const MAX_BATCH_SIZE: usize = 8;
+
+/// The maximum number of blocks with reclaims recorded in the reclaim history of an asset.
+pub const MAX_RECLAIM_HISTORY: usize = 64;
+
+/// The maximum number of asset ids, from `NextAssetId`, that `create_asset` tries.
+pub const MAX_ASSET_ID_SEARCH: u32 = 16;
+
+/// `PoolCapNearlyReached` and `ReclaimLimitNearlyReached` are emitted by the call that drops
+/// the headroom under the limit to `1 / HEADROOM_WARNING_DIVISOR` of the limit or less.
+/// A call rejected by the limit reverts together with its events, so the warning is given
+/// by the last calls that fit rather than by the rejected one.
+pub const HEADROOM_WARNING_DIVISOR: AssetBalance = 10;
+
+/// A memo attached to a private output, e.g., an invoice id or a payment reference.
+/// It is encrypted under the same recipient key as the note, and is opaque to the ledger.
+pub type EncryptedMemo = Vec<u8>;
+
+/// A private transfer of a `batch_private_transfer` call, with the same
+/// view tags, auditor notes and memos as the arguments of `private_transfer`.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BatchedTransfer {
+	/// The private transfer payload.
+	pub payload: PrivateTransferPayload,
+	/// The view tags of the two receiver notes.
+	pub view_tags: [Option<ViewTag>; 2],
+	/// The notes of the two receivers, encrypted to the auditor of the asset.
+	pub auditor_notes: [Option<MantaEciesCiphertext>; 2],
+	/// The memos of the two receivers.
+	pub memos: [Option<EncryptedMemo>; 2],
+}
+
+/// The Merkle membership witness of a coin commitment within its shard.
+/// This is what a wallet needs to build the sender side of a
+/// `private_transfer` or `reclaim` payload.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct CoinWitness {
+	/// The index of the shard that holds the commitment.
+	pub shard_index: u8,
+	/// The index of the commitment within the shard.
+	pub leaf_index: u64,
+	/// The serialized authentication path from the commitment to the shard root.
+	pub path: Vec<u8>,
+	/// The current root of the shard.
+	pub root: [u8; 32],
+}
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// How a debit that leaves a balance below the minimum balance of an asset is handled.
+/// A credit that leaves the receiver below the minimum balance always fails.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum DustPolicy {
+	/// The dust is removed from the debited account and from the total supply.
+	Burn,
+	/// The debit that would leave the dust fails.
+	Reject,
+}
+
+/// An operation that can be paused.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum PausableOperation {
+	/// `transfer_asset`
+	TransferAsset,
+	/// `mint_private_asset`
+	MintPrivateAsset,
+	/// `private_transfer`
+	PrivateTransfer,
+	/// `reclaim`
+	Reclaim,
+}
+
+impl PausableOperation {
+	/// All the operations that can be paused.
+	pub const ALL: [PausableOperation; 4] = [
+		PausableOperation::TransferAsset,
+		PausableOperation::MintPrivateAsset,
+		PausableOperation::PrivateTransfer,
+		PausableOperation::Reclaim,
+	];
+}
+
+/// The maximum `amount` of an asset that can be reclaimed within any `period` consecutive blocks.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ReclaimLimit<BlockNumber> {
+	/// The maximum amount reclaimed within a window.
+	pub amount: AssetBalance,
+	/// The length of a window, in blocks.
+	pub period: BlockNumber,
+}
+
+/// The details of an issued asset.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct AssetDetails<AccountId, Balance> {
+	/// The account that created the asset and administers it.
+	pub owner: AccountId,
+	/// The deposit reserved from the owner, refunded when the asset is destroyed.
+	pub deposit: Balance,
+}
+
 /// The module configuration trait.
 pub trait Config: frame_system::Config {
 	/// The overarching event type.
 	type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
 
+	/// The currency in which the asset creation deposits are reserved.
+	type Currency: ReservableCurrency<Self::AccountId>;
+
+	/// The deposit reserved from the creator of an asset in `create_asset`.
+	type AssetDeposit: Get<BalanceOf<Self>>;
+
+	/// The origin which may pause and unpause operations.
+	type PauseOrigin: EnsureOrigin<Self::Origin>;
+
+	/// The origin which may create new assets.
+	/// On success it returns the account that is credited with the total supply.
+	type CreateOrigin: EnsureOrigin<Self::Origin, Success = Self::AccountId>;
+
+	/// The proof system that verifies private transfers and reclaims, e.g., `Groth16`.
+	type Verifier: Verifier;
+
+	/// The hash of the shard Merkle trees of the coin ledger, e.g., `Pedersen`.
+	/// It must match the hash the circuits of `Verifier` are built with.
+	type LedgerHash: LedgerHash;
+
 	/// Weight information for extrinsics in this pallet.
 	type WeightInfo: WeightInfo;
 }
@@ -147,9 +354,18 @@ decl_module! {
 		type Error = Error<T>;
 
 		fn deposit_event() = default;
+
+		fn on_runtime_upgrade() -> Weight {
+			migrations::migrate_to_v2::<T>()
+		}
+
 		/// Issue a new class of fungible assets. There are, and will only ever be, `total`
-		/// such assets and they'll all belong to the `origin` initially. It will have an
-		/// identifier `AssetId` instance: this will be specified in the `Issued` event.
+		/// such assets and they'll all belong to the account returned by `CreateOrigin`
+		/// initially. It will have an identifier `AssetId` instance: this will be specified
+		/// in the `Issued` event.
+		///
+		/// The origin must be `CreateOrigin`. The shielded ledger shared by all the assets
+		/// is initialized with the first asset; creating further assets leaves it untouched.
 		/// # <weight>
 		/// - `O(1)`
 		/// - 1 storage mutation (codec `O(1)`).
@@ -157,93 +373,52 @@ decl_module! {
 		/// - 1 event.
 		/// # </weight>
 		#[weight = T::WeightInfo::init_asset()]
+		#[transactional]
 		fn init_asset(origin,
 			asset_id: AssetId,
 			total: AssetBalance
 		) {
+			let origin = T::CreateOrigin::ensure_origin(origin)?;
+			Self::do_issue(asset_id, origin, total, Zero::zero())?;
+		}
 
-			// if the asset_id has a total suply != 0, then this asset is initialized
-			ensure!(
-				!TotalSupply::contains_key(&asset_id),
-				<Error<T>>::AlreadyInitialized
-			);
-
-			let origin = ensure_signed(origin)?;
-
-			// for now we hard code the parameters generated from the following seed:
-			//  * hash parameter seed: [1u8; 32]
-			//  * commitment parameter seed: [2u8; 32]
-			// We may want to pass those two in for `init`
-			let hash_param = HashParam::deserialize(HASH_PARAM.data)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to init the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to init the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let hash_param_checksum = hash_param.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to init the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let commit_param_checksum = commit_param.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to init the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			HashParamChecksum::put(hash_param_checksum);
-			CommitParamChecksum::put(commit_param_checksum);
-
-			// push the checksums for ZKP verification keys to the ledger storage
-			//
-			// NOTE:
-			//    this is is generated via
-			//      let zkp_key = zkp::keys::manta_XXX_zkp_key_gen(&hash_param_seed, &commit_param_seed);
-			//
-			// for prototype, we use this function to generate the ZKP verification key
-			// for product we should use a MPC protocol to build the ZKP verification key
-			// and then deploy that vk
-			//
-			let transfer_key_digest = TRANSFER_PK.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to init the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			TransferZKPKeyChecksum::put(transfer_key_digest);
-
-			let reclaim_key_digest = RECLAIM_PK.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to init the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			ReclaimZKPKeyChecksum::put(reclaim_key_digest);
-
-			// deposit the event then update the storage
-			Self::deposit_event(RawEvent::Issued(asset_id, origin.clone(), total));
-
-			// coin_shards are 256 lists of commitments
-			let coin_shards = MantaPrivateAssetLedger::default();
-			CoinShards::put(coin_shards);
-
-			// initialize the asset with `total` number of supplies
-			// the total number of private asset (pool balance) remain 0
-			// the assets is credit to the sender's account
-			PoolBalance::insert(asset_id, 0);
-			TotalSupply::insert(asset_id, total);
-			<Balances<T>>::insert(&origin, asset_id, total);
-
-			VNList::put(Vec::<[u8; 32]>::new());
-			EncValueList::put(Vec::<MantaEciesCiphertext>::new());
-
+		/// Issue a new class of fungible assets under the next free `AssetId`.
+		/// The total supply is credited to the account returned by `CreateOrigin`, which
+		/// also has `AssetDeposit` reserved until the asset is destroyed.
+		/// The new identifier is specified in the `Issued` event.
+		/// The ids taken through `init_asset`, or by an asset still being destroyed, are skipped,
+		/// up to `MAX_ASSET_ID_SEARCH` ids.
+		/// # <weight>
+		/// - `O(MAX_ASSET_ID_SEARCH)` storage reads.
+		/// - 1 balance reservation.
+		/// - 5 storage writes (codec `O(1)`).
+		/// - 1 event.
+		/// # </weight>
+		#[weight = T::WeightInfo::create_asset()]
+		#[transactional]
+		fn create_asset(origin, total: AssetBalance) {
+			let owner = T::CreateOrigin::ensure_origin(origin)?;
+
+			// skip the ids that have already been claimed through `init_asset`,
+			// and the ids of the assets being destroyed, which still have their details
+			let mut asset_id = NextAssetId::get();
+			let mut searched = 1;
+			while TotalSupply::contains_key(&asset_id) || <Asset<T>>::contains_key(&asset_id) {
+				ensure!(searched < MAX_ASSET_ID_SEARCH, <Error<T>>::NoAvailableAssetId);
+				asset_id = asset_id
+					.checked_add(1)
+					.ok_or(<Error<T>>::NoAvailableAssetId)?;
+				searched += 1;
+			}
+			let next_asset_id = asset_id
+				.checked_add(1)
+				.ok_or(<Error<T>>::NoAvailableAssetId)?;
+
+			let deposit = T::AssetDeposit::get();
+			T::Currency::reserve(&owner, deposit)?;
+			NextAssetId::put(next_asset_id);
+
+			Self::do_issue(asset_id, owner, total, deposit)?;
 		}
 
 		/// Move some assets from one holder to another.
@@ -255,6 +430,7 @@ decl_module! {
 		/// - 1 event.
 		/// # </weight>
 		#[weight = T::WeightInfo::transfer_asset()]
+		#[transactional]
 		fn transfer_asset(origin,
 			target: <T::Lookup as StaticLookup>::Source,
 			asset_id: AssetId,
@@ -266,26 +442,57 @@ decl_module! {
 				TotalSupply::contains_key(&asset_id),
 				<Error<T>>::BasecoinNotInit
 			);
+			Self::ensure_not_paused(PausableOperation::TransferAsset, asset_id)?;
 			let origin = ensure_signed(origin)?;
 
 			let origin_account = origin.clone();
-			let origin_balance = <Balances<T>>::get(&origin_account, asset_id);
+			let origin_balance = <Balances<T>>::get(asset_id, &origin_account);
 			let target = T::Lookup::lookup(target)?;
 			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
 			ensure!(origin_balance >= amount, Error::<T>::BalanceLow);
+
+			// compute both balances before any write
+			let new_origin_balance = origin_balance
+				.checked_sub(amount)
+				.ok_or(Error::<T>::Underflow)?;
+			let target_balance = if target == origin_account {
+				new_origin_balance
+			} else {
+				<Balances<T>>::get(asset_id, &target)
+			};
+			let new_target_balance = target_balance
+				.checked_add(amount)
+				.ok_or(Error::<T>::Overflow)?;
+
+			if target != origin_account {
+				Self::write_balance(asset_id, &origin_account, new_origin_balance)?;
+			}
+			Self::write_credited_balance(asset_id, &target, new_target_balance)?;
+
+			// deposit the event after all the writes
 			Self::deposit_event(
-				RawEvent::Transferred(asset_id, origin, target.clone(), amount)
+				RawEvent::Transferred(asset_id, origin, target, amount)
 			);
-
-			// todo: figure out the different between insert and mutate.
-			<Balances<T>>::insert(origin_account, asset_id, origin_balance - amount);
-			<Balances<T>>::mutate(target, asset_id, |balance| *balance += amount);
 		}
 
-		/// Given an amount, and relevant data, mint the token to the ledger
-		#[weight = T::WeightInfo::mint_private_asset()]
+		/// Given an amount, and relevant data, mint the token to the ledger.
+		/// An optional `view_tag` of the encrypted note can be attached
+		/// for faster wallet scanning.
+		/// If the asset has an auditor key, `auditor_note` must carry the note
+		/// encrypted to the auditor key.
+		#[weight = {
+			let (_, notes) = <Module<T>>::ledger_sizes();
+			let cms: Vec<_> = MintData::deserialize(payload.as_ref())
+				.map(|data| data.cm)
+				.into_iter()
+				.collect();
+			T::WeightInfo::mint_private_asset(notes, <Module<T>>::shard_size(&cms))
+		}]
+		#[transactional]
 		fn mint_private_asset(origin,
-			payload: MintPayload
+			payload: MintPayload,
+			view_tag: Option<ViewTag>,
+			auditor_note: Option<MantaEciesCiphertext>,
 		) {
 			// todo: Implement the fix denomination method
 
@@ -301,55 +508,49 @@ decl_module! {
 				TotalSupply::contains_key(&input.asset_id),
 				<Error<T>>::BasecoinNotInit
 			);
+			Self::ensure_not_paused(PausableOperation::MintPrivateAsset, input.asset_id)?;
+			ensure!(
+				!AuditorKeys::contains_key(&input.asset_id) || auditor_note.is_some(),
+				<Error<T>>::AuditorNoteMissing
+			);
 
 			// get the original balance
 			let origin = ensure_signed(origin)?;
 			let origin_account = origin.clone();
-			let origin_balance = <Balances<T>>::get(&origin_account, input.asset_id);
+			let origin_balance = <Balances<T>>::get(input.asset_id, &origin_account);
 			ensure!(origin_balance >= input.amount, Error::<T>::BalanceLow);
-
-			// get the parameter checksum from the ledger
-			// and make sure the parameters match
-			let hash_param_checksum_local = HASH_PARAM.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to mint the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let commit_param_checksum_local = COMMIT_PARAM.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to mint the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let hash_param_checksum = HashParamChecksum::get();
-			let commit_param_checksum = CommitParamChecksum::get();
-			ensure!(
-				hash_param_checksum_local == hash_param_checksum,
-				<Error<T>>::ParamFail
-			);
-			ensure!(
-				commit_param_checksum_local == commit_param_checksum,
-				<Error<T>>::ParamFail
-			);
-
-			let hash_param = HashParam::deserialize(HASH_PARAM.data)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to mint the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to mint the asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
+			let new_origin_balance = origin_balance
+				.checked_sub(input.amount)
+				.ok_or(Error::<T>::Underflow)?;
+			let pool_balance = PoolBalance::get(input.asset_id);
+			let new_pool_balance = pool_balance
+				.checked_add(input.amount)
+				.ok_or(Error::<T>::Overflow)?;
+			let pool_cap_headroom = match PoolCaps::get(input.asset_id) {
+				Some(cap) => {
+					ensure!(new_pool_balance <= cap, <Error<T>>::PoolCapExceeded);
+					Self::headroom_warning(cap, pool_balance, new_pool_balance)
+				}
+				None => None,
+			};
+
+			// load the ledger hash parameters and make sure they match the ledger
+			let hash_param = Self::ledger_hash_param()?;
+
+			// load the commitment parameters and make sure they match the ledger
+			let commit_param = Self::commit_param()?;
+
+			// the points of the payload and of the auditor notes must be canonical, see `payload_check`
+			payload_check::check_mint(&input).map_err(<Error<T>>::from)?;
+			if let Some(note) = &auditor_note {
+				payload_check::check_note(note).map_err(<Error<T>>::from)?;
+			}
 
 			// check the validity of the commitment
 			let res = input.sanity(&commit_param)
 				.map_err::<DispatchError, _>(|e| {
 					log::error!(target: "manta-pay", "failed to mint the asset with error: {:?}", e);
-					<Error<T>>::MintFail.into()
+					<Error<T>>::SanityCheckFail.into()
 				})?;
 
 			ensure!(
@@ -365,33 +566,42 @@ decl_module! {
 			);
 
 			// update the shards
-			coin_shards.update(&input.cm, hash_param)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to mint the asset with error: {:?}", e);
-					<Error<T>>::LedgerUpdateFail.into()
-				})?;
+			ensure!(
+				T::LedgerHash::update(&mut coin_shards, &input.cm, &hash_param),
+				<Error<T>>::LedgerUpdateFail
+			);
 
 			// update enc_value_list
 			let mut enc_value_list = EncValueList::get();
+			let note_index = enc_value_list.len() as u64;
 			enc_value_list.push(input.encrypted_note);
-			let old_pool_balance = PoolBalance::get(input.asset_id);
 
 			// write back to ledger storage
-			Self::deposit_event(
-				RawEvent::Minted(input.asset_id, origin, input.amount)
-			);
-
 			CoinShards::put(coin_shards);
 			EncValueList::put(enc_value_list);
-			PoolBalance::mutate(
-				input.asset_id,
-				|balance| *balance = old_pool_balance + input.amount
-			);
-			<Balances<T>>::mutate(
-				origin_account,
-				input.asset_id,
-				|balance| *balance =  origin_balance - input.amount
+			if let Some(view_tag) = view_tag {
+				ViewTags::insert(note_index, view_tag);
+			}
+			if let Some(auditor_note) = auditor_note {
+				AuditorNotes::insert(note_index, auditor_note);
+			}
+			PoolBalance::insert(input.asset_id, new_pool_balance);
+			Self::write_balance(input.asset_id, &origin_account, new_origin_balance)?;
+
+			// deposit the event after all the writes
+			Self::deposit_event(
+				RawEvent::Minted(
+					input.asset_id,
+					origin,
+					input.amount,
+					input.cm,
+					input.cm[0],
+					note_index,
+				)
 			);
+			if let Some(headroom) = pool_cap_headroom {
+				Self::deposit_event(RawEvent::PoolCapNearlyReached(input.asset_id, headroom));
+			}
 		}
 
 
@@ -399,12 +609,40 @@ decl_module! {
 		/// sender's private tokens into two receiver tokens. A proof is required to
 		/// make sure that this transaction is valid.
 		/// Neither the values nor the identities is leaked during this process.
-		#[weight = T::WeightInfo::private_transfer()]
+		/// Optional `view_tags` of the two encrypted notes can be attached
+		/// for faster wallet scanning.
+		/// `auditor_notes` carry the two notes encrypted to the auditor key
+		/// of the asset, if there is one. The payload does not reveal the asset,
+		/// so the auditor notes cannot be required here.
+		/// Each receiver can also get an encrypted memo of at most `MAX_MEMO_SIZE` bytes.
+		#[weight = {
+			let (void_numbers, notes) = <Module<T>>::ledger_sizes();
+			let cms = PrivateTransferData::deserialize(payload.as_ref())
+				.map(|data| vec![data.receiver_1.cm, data.receiver_2.cm])
+				.unwrap_or_default();
+			T::WeightInfo::private_transfer(void_numbers, notes, <Module<T>>::shard_size(&cms))
+		}]
+		#[transactional]
 		fn private_transfer(origin,
 			payload: PrivateTransferPayload,
+			view_tags: [Option<ViewTag>; 2],
+			auditor_notes: [Option<MantaEciesCiphertext>; 2],
+			memos: [Option<EncryptedMemo>; 2],
 		) {
 			// this function does not know which asset_id is been transferred.
-			// so there will not be an initialization check
+			// so there will not be an initialization check.
+			// todo: check the asset id once the transfer circuit takes it as a public input
+
+			ensure!(
+				memos.iter().flatten().all(|memo| memo.len() <= MAX_MEMO_SIZE),
+				<Error<T>>::MemoTooLong
+			);
+
+			// the payload does not reveal the asset, so only a global pause applies
+			ensure!(
+				!Paused::contains_key(PausableOperation::PrivateTransfer, None::<AssetId>),
+				<Error<T>>::OperationPaused
+			);
 
 			let data = PrivateTransferData::deserialize(payload.as_ref())
 				.map_err::<DispatchError, _>(|e| {
@@ -414,35 +652,23 @@ decl_module! {
 
 			let origin = ensure_signed(origin)?;
 
-			// get the parameter checksum from the ledger
-			// and make sure the parameters match
-			let hash_param_checksum_local = HASH_PARAM.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to transfer the private asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let hash_param_checksum = HashParamChecksum::get();
-			ensure!(
-				hash_param_checksum_local == hash_param_checksum,
-				<Error<T>>::ParamFail
-			);
-			let hash_param = HashParam::deserialize(HASH_PARAM.data)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to transfer the private asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
+			// load the ledger hash parameters and make sure they match the ledger
+			let hash_param = Self::ledger_hash_param()?;
 
 			// check if vn_old already spent
 			let mut vn_list = VNList::get();
 			ensure!(
 				!vn_list.contains(&data.sender_1.void_number),
-				<Error<T>>::MantaCoinSpent
+				<Error<T>>::MantaCoinSpentSender1
 			);
 			vn_list.push(data.sender_1.void_number);
+			ensure!(
+				data.sender_2.void_number != data.sender_1.void_number,
+				<Error<T>>::DuplicateVoidNumber
+			);
 			ensure!(
 				!vn_list.contains(&data.sender_2.void_number),
-				<Error<T>>::MantaCoinSpent
+				<Error<T>>::MantaCoinSpentSender2
 			);
 			vn_list.push(data.sender_2.void_number);
 
@@ -459,62 +685,248 @@ decl_module! {
 			);
 
 			// check the commitment are not in the list already
-			// and update coin list
-			// with sharding, there is no point to batch update
-			// since the commitments are likely to go to different shards
 			ensure!(
 				!coin_shards.exist(&data.receiver_1.cm),
 				<Error<T>>::MantaCoinExist
 			);
-			coin_shards
-				.update(&data.receiver_1.cm, hash_param.clone())
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to transfer the private asset with error: {:?}", e);
-					<Error<T>>::LedgerUpdateFail.into()
-				})?;
-
 			ensure!(
 				!coin_shards.exist(&data.receiver_2.cm),
 				<Error<T>>::MantaCoinExist
 			);
-			coin_shards
-				.update(&data.receiver_2.cm, hash_param)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to transfer the private asset with error: {:?}", e);
-					<Error<T>>::LedgerUpdateFail.into()
-				})?;
-
-			// get the verification key from the ledger
-			let transfer_vk_checksum = TransferZKPKeyChecksum::get();
-			let transfer_vk = TRANSFER_PK;
-			let transfer_vk_checksum_local = transfer_vk.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to transfer the private asset with error: {:?}", e);
-					<Error<T>>::ZkpParamFail.into()
-				})?;
-
 			ensure!(
-				transfer_vk_checksum_local == transfer_vk_checksum,
-				<Error<T>>::ZkpParamFail,
+				data.receiver_1.cm != data.receiver_2.cm,
+				<Error<T>>::DuplicateCoin
 			);
 
+			// get the prepared verification key from the ledger
+			let transfer_vk = Self::transfer_key()?;
+
+			// the points of the payload and of the auditor notes must be canonical, see `payload_check`
+			payload_check::check_transfer(&data).map_err(<Error<T>>::from)?;
+			for note in auditor_notes.iter().flatten() {
+				payload_check::check_note(note).map_err(<Error<T>>::from)?;
+			}
+
 			// check validity of zkp
 			ensure!(
-				data.verify(&transfer_vk),
+				T::Verifier::verify_transfer(&transfer_vk, &data),
 				<Error<T>>::ZkpVerificationFail,
 			);
 
 			// TODO: revisit replay attack here
 
+			// update coin list
+			// with sharding, there is no point to batch update
+			// since the commitments are likely to go to different shards
+			ensure!(
+				T::LedgerHash::update(&mut coin_shards, &data.receiver_1.cm, &hash_param),
+				<Error<T>>::LedgerUpdateFail
+			);
+			ensure!(
+				T::LedgerHash::update(&mut coin_shards, &data.receiver_2.cm, &hash_param),
+				<Error<T>>::LedgerUpdateFail
+			);
+
 			// update ledger storage
 			let mut enc_value_list = EncValueList::get();
+			let note_index = enc_value_list.len() as u64;
 			enc_value_list.push(data.receiver_1.encrypted_note);
 			enc_value_list.push(data.receiver_2.encrypted_note);
 
-			Self::deposit_event(RawEvent::PrivateTransferred(origin));
 			CoinShards::put(coin_shards);
 			VNList::put(vn_list);
 			EncValueList::put(enc_value_list);
+			for (i, view_tag) in view_tags.iter().enumerate() {
+				if let Some(view_tag) = view_tag {
+					ViewTags::insert(note_index + i as u64, view_tag);
+				}
+			}
+			for (i, auditor_note) in auditor_notes.iter().enumerate() {
+				if let Some(auditor_note) = auditor_note {
+					AuditorNotes::insert(note_index + i as u64, auditor_note);
+				}
+			}
+			for (i, memo) in memos.iter().enumerate() {
+				if let Some(memo) = memo {
+					Memos::insert(note_index + i as u64, memo);
+				}
+			}
+
+			// deposit the event after all the writes
+			Self::deposit_event(
+				RawEvent::PrivateTransferred(
+					origin,
+					[data.sender_1.void_number, data.sender_2.void_number],
+					[data.receiver_1.cm, data.receiver_2.cm],
+					[data.receiver_1.cm[0], data.receiver_2.cm[0]],
+					[note_index, note_index + 1],
+				)
+			);
+		}
+
+		/// Apply a batch of private transfers, each moving values from two
+		/// sender's private tokens into two receiver tokens.
+		/// Every transfer is checked against the ledger state before the batch,
+		/// and no two transfers may spend the same void number or create the same coin.
+		/// The proofs of all the transfers are verified together, so the whole batch
+		/// fails if any of them is invalid.
+		/// Each transfer carries the view tags, auditor notes and memos of its two
+		/// receivers, as in `private_transfer`.
+		/// Until the batch benchmark runs over more than one precomputed transfer, each
+		/// transfer is charged the base weight of a `private_transfer`.
+		#[weight = {
+			let (void_numbers, notes) = <Module<T>>::ledger_sizes();
+			let cms: Vec<_> = transfers
+				.iter()
+				.filter_map(|transfer| PrivateTransferData::deserialize(transfer.payload.as_ref()).ok())
+				.flat_map(|data| vec![data.receiver_1.cm, data.receiver_2.cm])
+				.collect();
+			T::WeightInfo::batch_private_transfer(
+				transfers.len() as u32,
+				void_numbers,
+				notes,
+				<Module<T>>::shard_size(&cms),
+			)
+		}]
+		#[transactional]
+		fn batch_private_transfer(origin, transfers: Vec<BatchedTransfer>) {
+			ensure!(!transfers.is_empty(), <Error<T>>::EmptyBatch);
+			ensure!(transfers.len() <= MAX_BATCH_SIZE, <Error<T>>::BatchTooLarge);
+
+			ensure!(
+				transfers
+					.iter()
+					.flat_map(|transfer| transfer.memos.iter().flatten())
+					.all(|memo| memo.len() <= MAX_MEMO_SIZE),
+				<Error<T>>::MemoTooLong
+			);
+
+			// the payloads do not reveal the asset, so only a global pause applies
+			ensure!(
+				!Paused::contains_key(PausableOperation::PrivateTransfer, None::<AssetId>),
+				<Error<T>>::OperationPaused
+			);
+
+			let batch = transfers
+				.iter()
+				.map(|transfer| PrivateTransferData::deserialize(transfer.payload.as_ref()))
+				.collect::<Result<Vec<_>, _>>()
+				.map_err::<DispatchError, _>(|e| {
+					log::error!(target: "manta-pay", "failed to transfer the private assets with error: {:?}", e);
+					<Error<T>>::PayloadDesFail.into()
+				})?;
+
+			let origin = ensure_signed(origin)?;
+
+			// load the ledger hash parameters and make sure they match the ledger
+			let hash_param = Self::ledger_hash_param()?;
+
+			let mut vn_list = VNList::get();
+			let mut coin_shards = CoinShards::get();
+			let mut new_cms: Vec<[u8; 32]> = Vec::with_capacity(2 * batch.len());
+			for data in batch.iter() {
+				// check if vn_old already spent, in the ledger or earlier in the batch
+				ensure!(
+					!vn_list.contains(&data.sender_1.void_number),
+					<Error<T>>::MantaCoinSpentSender1
+				);
+				vn_list.push(data.sender_1.void_number);
+				ensure!(
+					data.sender_2.void_number != data.sender_1.void_number,
+					<Error<T>>::DuplicateVoidNumber
+				);
+				ensure!(
+					!vn_list.contains(&data.sender_2.void_number),
+					<Error<T>>::MantaCoinSpentSender2
+				);
+				vn_list.push(data.sender_2.void_number);
+
+				// the roots are checked against the ledger state before the batch
+				ensure!(
+					coin_shards.check_root(&data.sender_1.root),
+					<Error<T>>::InvalidLedgerState
+				);
+				ensure!(
+					coin_shards.check_root(&data.sender_2.root),
+					<Error<T>>::InvalidLedgerState
+				);
+
+				// check the commitment are not in the list already
+				for cm in [data.receiver_1.cm, data.receiver_2.cm].iter() {
+					ensure!(!coin_shards.exist(cm), <Error<T>>::MantaCoinExist);
+					ensure!(!new_cms.contains(cm), <Error<T>>::DuplicateCoin);
+					new_cms.push(*cm);
+				}
+			}
+
+			// get the prepared verification key from the ledger
+			let transfer_vk = Self::transfer_key()?;
+
+			// the points of the payloads and of the auditor notes must be canonical, see `payload_check`
+			for data in batch.iter() {
+				payload_check::check_transfer(data).map_err(<Error<T>>::from)?;
+			}
+			for note in transfers.iter().flat_map(|transfer| transfer.auditor_notes.iter().flatten()) {
+				payload_check::check_note(note).map_err(<Error<T>>::from)?;
+			}
+
+			// check validity of all the zkps at once
+			ensure!(
+				T::Verifier::batch_verify_transfers(&transfer_vk, &batch),
+				<Error<T>>::ZkpVerificationFail,
+			);
+
+			// update coin list
+			for cm in new_cms.iter() {
+				ensure!(
+					T::LedgerHash::update(&mut coin_shards, cm, &hash_param),
+					<Error<T>>::LedgerUpdateFail
+				);
+			}
+
+			// update ledger storage
+			let mut enc_value_list = EncValueList::get();
+			let first_note_index = enc_value_list.len() as u64;
+			for data in batch.iter() {
+				enc_value_list.push(data.receiver_1.encrypted_note);
+				enc_value_list.push(data.receiver_2.encrypted_note);
+			}
+
+			CoinShards::put(coin_shards);
+			VNList::put(vn_list);
+			EncValueList::put(enc_value_list);
+			for (i, transfer) in transfers.iter().enumerate() {
+				let note_index = first_note_index + 2 * i as u64;
+				for (j, view_tag) in transfer.view_tags.iter().enumerate() {
+					if let Some(view_tag) = view_tag {
+						ViewTags::insert(note_index + j as u64, view_tag);
+					}
+				}
+				for (j, auditor_note) in transfer.auditor_notes.iter().enumerate() {
+					if let Some(auditor_note) = auditor_note {
+						AuditorNotes::insert(note_index + j as u64, auditor_note);
+					}
+				}
+				for (j, memo) in transfer.memos.iter().enumerate() {
+					if let Some(memo) = memo {
+						Memos::insert(note_index + j as u64, memo);
+					}
+				}
+			}
+
+			// deposit the events after all the writes
+			for (i, data) in batch.iter().enumerate() {
+				let note_index = first_note_index + 2 * i as u64;
+				Self::deposit_event(
+					RawEvent::PrivateTransferred(
+						origin.clone(),
+						[data.sender_1.void_number, data.sender_2.void_number],
+						[data.receiver_1.cm, data.receiver_2.cm],
+						[data.receiver_1.cm[0], data.receiver_2.cm[0]],
+						[note_index, note_index + 1],
+					)
+				);
+			}
 		}
 
 		/// Manta's reclaim function that moves values from two
@@ -525,10 +937,30 @@ decl_module! {
 		/// except for the reclaimed amount.
 		/// At the moment, the reclaimed amount goes directly to `origin` account.
 		/// __TODO__: shall we use a different receiver rather than `origin`?
-		#[weight = T::WeightInfo::reclaim()]
+		/// An optional `view_tag` of the encrypted note can be attached
+		/// for faster wallet scanning.
+		/// If the asset has an auditor key, `auditor_note` must carry the note
+		/// encrypted to the auditor key.
+		/// The receiver can also get an encrypted memo of at most `MAX_MEMO_SIZE` bytes.
+		#[weight = {
+			let (void_numbers, notes) = <Module<T>>::ledger_sizes();
+			let cms: Vec<_> = ReclaimData::deserialize(payload.as_ref())
+				.map(|data| data.receiver.cm)
+				.into_iter()
+				.collect();
+			T::WeightInfo::reclaim(void_numbers, notes, <Module<T>>::shard_size(&cms))
+		}]
+		#[transactional]
 		fn reclaim(origin,
 			payload: ReclaimPayload,
+			view_tag: Option<ViewTag>,
+			auditor_note: Option<MantaEciesCiphertext>,
+			memo: Option<EncryptedMemo>,
 		) {
+			ensure!(
+				memo.as_ref().map_or(true, |memo| memo.len() <= MAX_MEMO_SIZE),
+				<Error<T>>::MemoTooLong
+			);
 
 			let data = ReclaimData::deserialize(payload.as_ref())
 				.map_err::<DispatchError, _>(|e| {
@@ -541,64 +973,73 @@ decl_module! {
 				TotalSupply::contains_key(&data.asset_id),
 				<Error<T>>::BasecoinNotInit
 			);
+			Self::ensure_not_paused(PausableOperation::Reclaim, data.asset_id)?;
+			ensure!(
+				!AuditorKeys::contains_key(&data.asset_id) || auditor_note.is_some(),
+				<Error<T>>::AuditorNoteMissing
+			);
 
 			let origin = ensure_signed(origin)?;
 			let origin_account = origin.clone();
-			let origin_balance = <Balances<T>>::get(&origin, data.asset_id);
+			let origin_balance = <Balances<T>>::get(data.asset_id, &origin);
 
-			// get the parameter checksum from the ledger
-			// and make sure the parameters match
-			let hash_param_checksum_local = HASH_PARAM.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to reclaim the private asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
-
-			let hash_param_checksum = HashParamChecksum::get();
-			ensure!(
-				hash_param_checksum_local == hash_param_checksum,
-				<Error<T>>::ParamFail
-			);
-			let hash_param = HashParam::deserialize(HASH_PARAM.data)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to reclaim the private asset with error: {:?}", e);
-					<Error<T>>::ParamFail.into()
-				})?;
+			// load the ledger hash parameters and make sure they match the ledger
+			let hash_param = Self::ledger_hash_param()?;
 
 			// check the balance is greater than amount
-			let mut pool = PoolBalance::get(data.asset_id);
-			ensure!(pool>=data.reclaim_amount, <Error<T>>::PoolOverdrawn);
-			pool -= data.reclaim_amount;
+			let pool = PoolBalance::get(data.asset_id)
+				.checked_sub(data.reclaim_amount)
+				.ok_or(<Error<T>>::PoolOverdrawn)?;
+			let new_origin_balance = origin_balance
+				.checked_add(data.reclaim_amount)
+				.ok_or(<Error<T>>::Overflow)?;
+
+			// check the amount reclaimed within the last `period` blocks stays within the limit
+			let reclaim_history = match <ReclaimLimits<T>>::get(data.asset_id) {
+				Some(limit) => {
+					let now = <frame_system::Module<T>>::block_number();
+					let mut history = <ReclaimHistory<T>>::get(data.asset_id);
+					history.retain(|(block, _)| now < block.saturating_add(limit.period));
+					let reclaimed = history
+						.iter()
+						.try_fold(data.reclaim_amount, |sum, (_, amount)| sum.checked_add(*amount))
+						.ok_or(<Error<T>>::Overflow)?;
+					ensure!(reclaimed <= limit.amount, <Error<T>>::ReclaimLimitExceeded);
+					Self::record_reclaim(&mut history, now, data.reclaim_amount);
+					let warning = Self::headroom_warning(
+						limit.amount,
+						reclaimed.saturating_sub(data.reclaim_amount),
+						reclaimed,
+					)
+					.zip(history.first().map(|(block, _)| *block));
+					Some((history, warning))
+				}
+				None => None,
+			};
 
 			// check if sn_old already spent
 			let mut vn_list = VNList::get();
 			ensure!(
 				!vn_list.contains(&data.sender_1.void_number),
-				<Error<T>>::MantaCoinSpent
+				<Error<T>>::MantaCoinSpentSender1
 			);
 			vn_list.push(data.sender_1.void_number);
+			ensure!(
+				data.sender_2.void_number != data.sender_1.void_number,
+				<Error<T>>::DuplicateVoidNumber
+			);
 			ensure!(
 				!vn_list.contains(&data.sender_2.void_number),
-				<Error<T>>::MantaCoinSpent
+				<Error<T>>::MantaCoinSpentSender2
 			);
 			vn_list.push(data.sender_2.void_number);
 
 			// get the coin list
 			let mut coin_shards = CoinShards::get();
 
-			// get the verification key from the ledger
-			let reclaim_vk_checksum = ReclaimZKPKeyChecksum::get();
-			let reclaim_vk = RECLAIM_PK;
-			let reclaim_vk_checksum_local = reclaim_vk.get_checksum()
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to reclaim the private asset with error: {:?}", e);
-					<Error<T>>::ZkpParamFail.into()
-				})?;
+			// get the prepared verification key from the ledger
+			let reclaim_vk = Self::reclaim_key()?;
 
-			ensure!(
-				reclaim_vk_checksum_local == reclaim_vk_checksum,
-				<Error<T>>::ZkpParamFail
-			);
 			// get the ledger state from the ledger
 			// and check the validity of the state
 			ensure!(
@@ -612,13 +1053,19 @@ decl_module! {
 			// check the commitment are not in the list already
 			ensure!(
 				!coin_shards.exist(&data.receiver.cm),
-				<Error<T>>::MantaCoinSpent
+				<Error<T>>::MantaCoinExist
 			);
 
 
+			// the points of the payload and of the auditor notes must be canonical, see `payload_check`
+			payload_check::check_reclaim(&data).map_err(<Error<T>>::from)?;
+			if let Some(note) = &auditor_note {
+				payload_check::check_note(note).map_err(<Error<T>>::from)?;
+			}
+
 			// check validity of zkp
 			ensure!(
-				data.verify(&reclaim_vk),
+				T::Verifier::verify_reclaim(&reclaim_vk, &data),
 				<Error<T>>::ZkpVerificationFail,
 			);
 
@@ -626,28 +1073,279 @@ decl_module! {
 
 			// update ledger storage
 			let mut enc_value_list = EncValueList::get();
+			let note_index = enc_value_list.len() as u64;
 			enc_value_list.push(data.receiver.encrypted_note);
 
-			coin_shards
-				.update(&data.receiver.cm, hash_param)
-				.map_err::<DispatchError, _>(|e| {
-					log::error!(target: "manta-pay", "failed to reclaim the private asset with error: {:?}", e);
-					<Error<T>>::LedgerUpdateFail.into()
-				})?;
+			ensure!(
+				T::LedgerHash::update(&mut coin_shards, &data.receiver.cm, &hash_param),
+				<Error<T>>::LedgerUpdateFail
+			);
 
 			CoinShards::put(coin_shards);
-
-			Self::deposit_event(
-				RawEvent::PrivateReclaimed(data.asset_id, origin, data.reclaim_amount)
-			);
 			VNList::put(vn_list);
-			PoolBalance::mutate(data.asset_id, |balance| *balance = pool);
+			PoolBalance::insert(data.asset_id, pool);
 			EncValueList::put(enc_value_list);
-			<Balances<T>>::mutate(
-				origin_account,
-				data.asset_id,
-				|balance| *balance = origin_balance + data.reclaim_amount
+			if let Some(view_tag) = view_tag {
+				ViewTags::insert(note_index, view_tag);
+			}
+			if let Some(auditor_note) = auditor_note {
+				AuditorNotes::insert(note_index, auditor_note);
+			}
+			if let Some(memo) = memo {
+				Memos::insert(note_index, memo);
+			}
+			Self::write_credited_balance(data.asset_id, &origin_account, new_origin_balance)?;
+			if let Some((history, _)) = &reclaim_history {
+				<ReclaimHistory<T>>::insert(data.asset_id, history);
+			}
+
+			// deposit the event after all the writes
+			Self::deposit_event(
+				RawEvent::PrivateReclaimed(
+					data.asset_id,
+					origin,
+					data.reclaim_amount,
+					[data.sender_1.void_number, data.sender_2.void_number],
+					data.receiver.cm,
+					data.receiver.cm[0],
+					note_index,
+				)
+			);
+			if let Some((_, Some((headroom, window_start)))) = reclaim_history {
+				Self::deposit_event(
+					RawEvent::ReclaimLimitNearlyReached(data.asset_id, headroom, window_start)
+				);
+			}
+		}
+
+		/// Register, or remove with `None`, the auditor key of the asset `asset_id`.
+		/// Once registered, every note of this asset must also be encrypted to the
+		/// auditor key, so that the auditor can decrypt the whole private history
+		/// of this asset without holding any spending key.
+		///
+		/// The pallet only checks that the auditor notes are present: it cannot check
+		/// that an auditor note encrypts the same value as the note of the receiver,
+		/// so the history of the auditor is what the senders declared.
+		/// The auditor notes are required by `mint_private_asset` and `reclaim`, whose
+		/// payloads reveal the asset. A private transfer does not reveal its asset,
+		/// so its auditor notes cannot be required until the transfer circuit binds
+		/// the asset id.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		#[weight = T::WeightInfo::set_auditor_key()]
+		#[transactional]
+		fn set_auditor_key(origin,
+			asset_id: AssetId,
+			auditor_key: Option<[u8; 32]>,
+		) {
+			ensure_root(origin)?;
+
+			ensure!(
+				TotalSupply::contains_key(&asset_id),
+				<Error<T>>::BasecoinNotInit
+			);
+
+			match auditor_key {
+				Some(key) => AuditorKeys::insert(asset_id, key),
+				None => AuditorKeys::remove(asset_id),
+			}
+
+			Self::deposit_event(RawEvent::AuditorKeySet(asset_id, auditor_key));
+		}
+
+		/// Set the minimum balance of the asset `asset_id`, or remove it with a zero
+		/// `min_balance`. A debit that leaves an account with a non-zero balance below
+		/// the minimum is handled by `dust_policy`: the dust is either burned or the
+		/// debit is rejected. A credit that leaves the receiver below the minimum is
+		/// always rejected. Balances that are already below the minimum are only
+		/// affected by their next update.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		#[weight = T::WeightInfo::set_min_balance()]
+		#[transactional]
+		fn set_min_balance(origin,
+			asset_id: AssetId,
+			min_balance: AssetBalance,
+			dust_policy: DustPolicy,
+		) {
+			ensure_root(origin)?;
+
+			ensure!(
+				TotalSupply::contains_key(&asset_id),
+				<Error<T>>::BasecoinNotInit
+			);
+
+			if min_balance.is_zero() {
+				MinBalances::remove(asset_id);
+			} else {
+				MinBalances::insert(asset_id, (min_balance, dust_policy));
+			}
+
+			Self::deposit_event(RawEvent::MinBalanceSet(asset_id, min_balance, dust_policy));
+		}
+
+		/// Set the maximum pool balance of the asset `asset_id`, or remove it with `None`.
+		/// `mint_private_asset` fails once the pool balance would exceed the cap.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		#[weight = T::WeightInfo::set_pool_cap()]
+		#[transactional]
+		fn set_pool_cap(origin,
+			asset_id: AssetId,
+			pool_cap: Option<AssetBalance>,
+		) {
+			ensure_root(origin)?;
+
+			ensure!(
+				TotalSupply::contains_key(&asset_id),
+				<Error<T>>::BasecoinNotInit
+			);
+
+			match pool_cap {
+				Some(cap) => PoolCaps::insert(asset_id, cap),
+				None => PoolCaps::remove(asset_id),
+			}
+
+			Self::deposit_event(RawEvent::PoolCapSet(asset_id, pool_cap));
+		}
+
+		/// Set the maximum amount of the asset `asset_id` that can be reclaimed within any
+		/// `period` consecutive blocks, or remove it with `None`. A period of one block limits
+		/// the amount reclaimed per block. The window is rolling: a reclaim counts against
+		/// the limit until `period` blocks after the block it was included in.
+		/// Setting or removing the limit clears the reclaim history of the asset.
+		///
+		/// The dispatch origin for this call must be _Root_.
+		#[weight = T::WeightInfo::set_reclaim_limit()]
+		#[transactional]
+		fn set_reclaim_limit(origin,
+			asset_id: AssetId,
+			reclaim_limit: Option<ReclaimLimit<T::BlockNumber>>,
+		) {
+			ensure_root(origin)?;
+
+			ensure!(
+				TotalSupply::contains_key(&asset_id),
+				<Error<T>>::BasecoinNotInit
+			);
+
+			match &reclaim_limit {
+				Some(limit) => {
+					ensure!(!limit.period.is_zero(), <Error<T>>::ReclaimPeriodZero);
+					<ReclaimLimits<T>>::insert(asset_id, limit);
+				}
+				None => <ReclaimLimits<T>>::remove(asset_id),
+			}
+			<ReclaimHistory<T>>::remove(asset_id);
+
+			Self::deposit_event(RawEvent::ReclaimLimitSet(asset_id, reclaim_limit));
+		}
+
+		/// Pause `operation` for the asset `asset_id`, or for all the assets with `None`.
+		/// A paused call fails with `OperationPaused`. `private_transfer` can only be
+		/// paused for all the assets, since its payload does not reveal the asset.
+		///
+		/// The dispatch origin for this call must be `PauseOrigin`.
+		#[weight = T::WeightInfo::pause()]
+		#[transactional]
+		fn pause(origin,
+			operation: PausableOperation,
+			asset_id: Option<AssetId>,
+		) {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				operation != PausableOperation::PrivateTransfer || asset_id.is_none(),
+				<Error<T>>::UnsupportedPauseScope
 			);
+
+			Paused::insert(operation, asset_id, ());
+
+			Self::deposit_event(RawEvent::Paused(operation, asset_id));
+		}
+
+		/// Unpause `operation` for the asset `asset_id`, or for all the assets with `None`.
+		/// A global pause and a per-asset pause are lifted separately.
+		///
+		/// The dispatch origin for this call must be `PauseOrigin`.
+		#[weight = T::WeightInfo::unpause()]
+		#[transactional]
+		fn unpause(origin,
+			operation: PausableOperation,
+			asset_id: Option<AssetId>,
+		) {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			Paused::remove(operation, asset_id);
+
+			Self::deposit_event(RawEvent::Unpaused(operation, asset_id));
+		}
+
+		/// Destroy the asset `asset_id`, removing at most `max_accounts` balances per call.
+		///
+		/// The first call removes the total supply, the pool balance, the settings and the pauses
+		/// of the asset, after which the asset can no longer be used. Every call then removes up to `max_accounts`
+		/// balances and emits `PartiallyDestroyed` while some are left. The call that removes
+		/// the last balance also removes the asset details, refunds the creation deposit and
+		/// emits `Destroyed`.
+		///
+		/// The dispatch origin for this call must be the owner of the asset, and the asset
+		/// must have no private coins outstanding, i.e., its pool balance must be zero.
+		/// The assets issued before `Releases::V2` have no recorded owner, so they cannot be destroyed.
+		/// # <weight>
+		/// - `O(A)` where `A` is `max_accounts`.
+		/// - `A + 4` storage reads and `A + 13` storage writes.
+		/// - 1 event.
+		/// # </weight>
+		#[weight = T::WeightInfo::destroy_asset(*max_accounts)]
+		#[transactional]
+		fn destroy_asset(origin,
+			asset_id: AssetId,
+			max_accounts: u32,
+		) {
+			let origin = ensure_signed(origin)?;
+
+			let details = <Asset<T>>::get(&asset_id).ok_or(<Error<T>>::BasecoinNotInit)?;
+			ensure!(details.owner == origin, <Error<T>>::NoPermission);
+
+			// the first call freezes the asset
+			if TotalSupply::contains_key(&asset_id) {
+				ensure!(
+					PoolBalance::get(&asset_id).is_zero(),
+					<Error<T>>::PoolNotEmpty
+				);
+				TotalSupply::remove(&asset_id);
+				PoolBalance::remove(&asset_id);
+				AuditorKeys::remove(&asset_id);
+				MinBalances::remove(&asset_id);
+				PoolCaps::remove(&asset_id);
+				<ReclaimLimits<T>>::remove(&asset_id);
+				<ReclaimHistory<T>>::remove(&asset_id);
+				for operation in PausableOperation::ALL.iter() {
+					Paused::remove(operation, Some(asset_id));
+				}
+			}
+
+			let accounts: Vec<T::AccountId> = <Balances<T>>::iter_prefix(&asset_id)
+				.take(max_accounts as usize)
+				.map(|(who, _)| who)
+				.collect();
+			for who in accounts.iter() {
+				<Balances<T>>::remove(&asset_id, who);
+			}
+
+			if <Balances<T>>::iter_prefix(&asset_id).next().is_some() {
+				Self::deposit_event(
+					RawEvent::PartiallyDestroyed(asset_id, accounts.len() as u32)
+				);
+				return Ok(());
+			}
+
+			<Asset<T>>::remove(&asset_id);
+			T::Currency::unreserve(&details.owner, details.deposit);
+
+			// deposit the event after all the writes
+			Self::deposit_event(RawEvent::Destroyed(asset_id, details.owner));
 		}
 	}
 }
@@ -655,64 +1353,171 @@ decl_module! {
 decl_event! {
 	pub enum Event<T> where
 		<T as frame_system::Config>::AccountId,
+		<T as frame_system::Config>::BlockNumber,
 	{
 		/// The asset was issued. \[asset_id, owner, total_supply\]
 		Issued(AssetId, AccountId, AssetBalance),
 		/// The asset was transferred. \[from, to, amount\]
 		Transferred(AssetId, AccountId, AccountId, AssetBalance),
-		/// The asset was minted to private
-		Minted(AssetId, AccountId, AssetBalance),
-		/// Private transfer
-		PrivateTransferred(AccountId),
-		/// The assets was reclaimed
-		PrivateReclaimed(AssetId, AccountId, AssetBalance),
+		/// The asset was minted to private.
+		/// \[asset_id, from, amount, commitment, shard_index, note_index\]
+		Minted(AssetId, AccountId, AssetBalance, [u8; 32], u8, u64),
+		/// Private transfer.
+		/// \[origin, void_numbers, commitments, shard_indices, note_indices\]
+		PrivateTransferred(AccountId, [MantaRandomValue; 2], [[u8; 32]; 2], [u8; 2], [u64; 2]),
+		/// The assets was reclaimed.
+		/// \[asset_id, to, amount, void_numbers, commitment, shard_index, note_index\]
+		PrivateReclaimed(AssetId, AccountId, AssetBalance, [MantaRandomValue; 2], [u8; 32], u8, u64),
+		/// The auditor key of the asset was set or removed. \[asset_id, auditor_key\]
+		AuditorKeySet(AssetId, Option<[u8; 32]>),
+		/// The minimum balance of the asset was set, or removed if zero.
+		/// \[asset_id, min_balance, dust_policy\]
+		MinBalanceSet(AssetId, AssetBalance, DustPolicy),
+		/// A balance below the minimum balance was burned. \[asset_id, who, amount\]
+		DustBurned(AssetId, AccountId, AssetBalance),
+		/// The maximum pool balance of the asset was set or removed. \[asset_id, pool_cap\]
+		PoolCapSet(AssetId, Option<AssetBalance>),
+		/// The pool balance of the asset came close to its cap, see `HEADROOM_WARNING_DIVISOR`.
+		/// \[asset_id, headroom\]
+		PoolCapNearlyReached(AssetId, AssetBalance),
+		/// The reclaim limit of the asset was set or removed. \[asset_id, reclaim_limit\]
+		ReclaimLimitSet(AssetId, Option<ReclaimLimit<BlockNumber>>),
+		/// The amount reclaimed within the last `period` blocks came close to the reclaim limit
+		/// of the asset, see `HEADROOM_WARNING_DIVISOR`. More of the limit is freed when the
+		/// reclaims of `window_start` expire.
+		/// \[asset_id, headroom, window_start\]
+		ReclaimLimitNearlyReached(AssetId, AssetBalance, BlockNumber),
+		/// The operation was paused, for all the assets if `None`. \[operation, asset_id\]
+		Paused(PausableOperation, Option<AssetId>),
+		/// The operation was unpaused, for all the assets if `None`. \[operation, asset_id\]
+		Unpaused(PausableOperation, Option<AssetId>),
+		/// Some balances of an asset being destroyed were removed, more are left.
+		/// \[asset_id, removed_accounts\]
+		PartiallyDestroyed(AssetId, u32),
+		/// The asset was destroyed and the deposit refunded to the owner. \[asset_id, owner\]
+		Destroyed(AssetId, AccountId),
 	}
 }
 
 decl_error! {
 	/// Error messages.
 	pub enum Error for Module<T: Config> {
-		/// This token has already been initiated
+		/// The asset has already been initialized
 		AlreadyInitialized,
-		/// Transfer when not initialized
+		/// The asset has not been initialized
 		BasecoinNotInit,
 		/// Transfer amount should be non-zero
 		AmountZero,
 		/// Account balance must be greater than or equal to the transfer amount
 		BalanceLow,
-		/// Balance should be non-zero
-		BalanceZero,
-		/// Mint failure
+		/// The commitment of the minted coin does not match its asset id and amount
 		MintFail,
-		/// Mint failure
+		/// The sanity check of the mint payload could not be evaluated
+		SanityCheckFail,
+		/// The commitment could not be inserted into the ledger
 		LedgerUpdateFail,
-		/// MantaCoin exist
+		/// The output commitment is already in the ledger
 		MantaCoinExist,
-		/// MantaCoin does not exist
-		MantaNotCoinExist,
-		/// MantaCoin already spend
-		MantaCoinSpent,
-		/// ZKP parameter failed
-		ZkpParamFail,
+		/// The two output commitments of a private transfer are identical
+		DuplicateCoin,
+		/// The void number of the first sender has already been spent
+		MantaCoinSpentSender1,
+		/// The void number of the second sender has already been spent
+		MantaCoinSpentSender2,
+		/// The two senders of a private transfer or a reclaim spend the same coin
+		DuplicateVoidNumber,
+		/// The checksum of the ZKP verification key could not be computed
+		ZkpKeyChecksumFail,
+		/// The ZKP verification key does not match the checksum stored in the ledger
+		ZkpKeyMismatch,
 		/// ZKP verification failed
 		ZkpVerificationFail,
-		/// invalid ledger state
+		/// The sender refers to a ledger root that is not in the ledger
 		InvalidLedgerState,
-		/// Pool overdrawn
+		/// The reclaimed amount exceeds the pool balance
 		PoolOverdrawn,
-		/// Invalid parameters
-		ParamFail,
+		/// The hash parameters could not be deserialized
+		HashParamDesFail,
+		/// The commitment parameters could not be deserialized
+		CommitParamDesFail,
+		/// The checksum of the hash parameters could not be computed
+		HashParamChecksumFail,
+		/// The checksum of the commitment parameters could not be computed
+		CommitParamChecksumFail,
+		/// The hash parameters do not match the checksum stored in the ledger
+		HashParamMismatch,
+		/// The commitment parameters do not match the checksum stored in the ledger
+		CommitParamMismatch,
 		/// Payload deserialization fail
 		PayloadDesFail,
+		/// The asset has an auditor key but the note for the auditor is missing
+		AuditorNoteMissing,
+		/// The encrypted memo is longer than `MAX_MEMO_SIZE`
+		MemoTooLong,
+		/// A balance update would overflow
+		Overflow,
+		/// A balance update would underflow
+		Underflow,
+		/// No free asset id was found within `MAX_ASSET_ID_SEARCH` ids from `NextAssetId`
+		NoAvailableAssetId,
+		/// The origin is not the owner of the asset
+		NoPermission,
+		/// The asset still has private coins outstanding
+		PoolNotEmpty,
+		/// The debit would leave a balance below the minimum balance of the asset
+		BalanceBelowMinimum,
+		/// The credit would leave the receiver below the minimum balance of the asset
+		BelowMinBalance,
+		/// The mint would exceed the maximum pool balance of the asset
+		PoolCapExceeded,
+		/// The reclaim would exceed the amount that can be reclaimed within the last `period` blocks
+		ReclaimLimitExceeded,
+		/// The period of a reclaim limit must be at least one block
+		ReclaimPeriodZero,
+		/// The operation is paused, for this asset or for all the assets
+		OperationPaused,
+		/// The operation cannot be paused for a single asset
+		UnsupportedPauseScope,
+		/// The batch of private transfers is empty
+		EmptyBatch,
+		/// The batch has more than `MAX_BATCH_SIZE` private transfers
+		BatchTooLarge,
+		/// The ZKP verification key could not be prepared
+		ZkpKeyPrepareFail,
+		/// A point of the payload has a coordinate that is not a canonical field element
+		NonCanonicalFieldElement,
+		/// A point of the payload is not on the curve
+		PointNotOnCurve,
+		/// A point of the payload is not in the prime order subgroup
+		PointNotInSubgroup,
+		/// A point of the payload is not in its canonical encoding
+		NonCanonicalPoint,
+		/// The proof of the payload is not in its canonical encoding, or is degenerate
+		MalleatedProof,
+	}
+}
+
+impl<T: Config> From<payload_check::PayloadError> for Error<T> {
+	fn from(e: payload_check::PayloadError) -> Self {
+		use payload_check::PayloadError::*;
+		match e {
+			NonCanonicalFieldElement => Error::<T>::NonCanonicalFieldElement,
+			PointNotOnCurve => Error::<T>::PointNotOnCurve,
+			PointNotInSubgroup => Error::<T>::PointNotInSubgroup,
+			NonCanonicalPoint => Error::<T>::NonCanonicalPoint,
+			MalleatedProof => Error::<T>::MalleatedProof,
+		}
 	}
 }
 
 decl_storage! {
 	trait Store for Module<T: Config> as Assets {
 		/// The number of units of assets held by any given account.
+		/// Keyed by the asset first, so that the balances of an asset
+		/// can be removed when it is destroyed.
 		pub Balances: double_map
-			hasher(blake2_128_concat) T::AccountId,
-			hasher(blake2_128_concat) AssetId
+			hasher(blake2_128_concat) AssetId,
+			hasher(blake2_128_concat) T::AccountId
 			=> AssetBalance;
 
 		/// The total unit supply of the asset.
@@ -734,9 +1539,59 @@ decl_storage! {
 		/// List of encrypted values.
 		pub EncValueList get(fn enc_value_list): Vec<MantaEciesCiphertext>;
 
+		/// The view tags, keyed by the index in `EncValueList` of the note they belong to.
+		/// Notes without a view tag have no entry, so the view tags are not read by the private calls.
+		pub ViewTags get(fn view_tag): map hasher(twox_64_concat) u64 => Option<ViewTag>;
+
+		/// The serialized ECIES public key of the auditor of an asset.
+		pub AuditorKeys get(fn auditor_key): map hasher(blake2_128_concat) AssetId => Option<[u8; 32]>;
+
+		/// The notes encrypted to the auditor keys, keyed by the index in `EncValueList`
+		/// of the note they belong to. Notes without an auditor note have no entry.
+		pub AuditorNotes get(fn auditor_note): map hasher(twox_64_concat) u64 => Option<MantaEciesCiphertext>;
+
+		/// The encrypted memos, keyed by the index in `EncValueList` of the note they belong to.
+		/// Notes without a memo have no entry, so the memos are not read by the private calls.
+		pub Memos get(fn memo): map hasher(twox_64_concat) u64 => Option<EncryptedMemo>;
+
+		/// The minimum balance of an asset, and how the dust below it is handled.
+		pub MinBalances get(fn min_balance): map hasher(blake2_128_concat) AssetId
+			=> Option<(AssetBalance, DustPolicy)>;
+
+		/// The maximum pool balance of an asset.
+		pub PoolCaps get(fn pool_cap): map hasher(blake2_128_concat) AssetId => Option<AssetBalance>;
+
+		/// The maximum amount of an asset that can be reclaimed within a window of blocks.
+		pub ReclaimLimits get(fn reclaim_limit): map hasher(blake2_128_concat) AssetId
+			=> Option<ReclaimLimit<T::BlockNumber>>;
+
+		/// The amounts of an asset reclaimed in each block of the current reclaim window,
+		/// oldest first, for at most `MAX_RECLAIM_HISTORY` blocks.
+		pub ReclaimHistory get(fn reclaim_history): map hasher(blake2_128_concat) AssetId
+			=> Vec<(T::BlockNumber, AssetBalance)>;
+
+		/// The paused operations, per asset or for all the assets with `None`.
+		pub Paused get(fn paused): double_map
+			hasher(blake2_128_concat) PausableOperation,
+			hasher(blake2_128_concat) Option<AssetId>
+			=> Option<()>;
+
+		/// The details of the issued assets.
+		pub Asset get(fn asset): map hasher(blake2_128_concat) AssetId
+			=> Option<AssetDetails<T::AccountId, BalanceOf<T>>>;
+
+		/// The next `AssetId` to try in `create_asset`.
+		pub NextAssetId get(fn next_asset_id): AssetId;
+
 		/// The balance of all minted coins for this asset_id.
 		pub PoolBalance: map hasher(blake2_128_concat) AssetId => AssetBalance;
 
+		/// Whether the shielded ledger shared by all the assets has been initialized.
+		pub LedgerInitialized get(fn ledger_initialized): bool;
+
+		/// The layout of the storage, see `migrations`.
+		pub StorageVersion get(fn storage_version): Releases;
+
 		/// The checksum of hash parameter.
 		pub HashParamChecksum get(fn hash_param_checksum): [u8; 32];
 
@@ -752,6 +1607,18 @@ decl_storage! {
 		/// At the moment we are storing the whole serialized key
 		/// in the blockchain storage.
 		pub ReclaimZKPKeyChecksum get(fn reclaim_zkp_vk_checksum): [u8; 32];
+
+		/// The transfer verification key prepared by `Config::Verifier`,
+		/// with the checksum of the key it was prepared from.
+		/// For `Groth16` it is the key and `e(alpha, beta)`, about 3 KB, and every private transfer
+		/// reads and decodes it. The line coefficients of the key, about 40 KB, are prepared by
+		/// each call instead of being read: the `prepared verifying key` bench of `manta_bench`
+		/// compares this with preparing the whole key on every call.
+		pub PreparedTransferKey get(fn prepared_transfer_vk): Option<([u8; 32], Vec<u8>)>;
+
+		/// The reclaim verification key prepared by `Config::Verifier`,
+		/// with the checksum of the key it was prepared from.
+		pub PreparedReclaimKey get(fn prepared_reclaim_vk): Option<([u8; 32], Vec<u8>)>;
 	}
 }
 
@@ -761,11 +1628,368 @@ impl<T: Config> Module<T> {
 
 	/// Get the asset `id` balance of `who`.
 	pub fn balance(who: T::AccountId, what: AssetId) -> AssetBalance {
-		<Balances<T>>::get(who, what)
+		<Balances<T>>::get(what, who)
 	}
 
 	/// Get the asset `id` total supply.
 	pub fn total_supply(what: AssetId) -> AssetBalance {
 		TotalSupply::get(what)
 	}
+
+	/// Get the asset `id` balance held in private coins.
+	pub fn pool_balance(what: AssetId) -> AssetBalance {
+		PoolBalance::get(what)
+	}
+
+	/// Get the asset `id` balance held in public accounts,
+	/// i.e., the total supply minus the pool balance.
+	pub fn public_supply(what: AssetId) -> AssetBalance {
+		TotalSupply::get(what).saturating_sub(PoolBalance::get(what))
+	}
+
+	/// Check if the asset `id` is initialized.
+	pub fn is_initialized(what: AssetId) -> bool {
+		TotalSupply::contains_key(what)
+	}
+
+	/// Get the number of void numbers that have been revealed.
+	pub fn void_number_count() -> usize {
+		VNList::get().len()
+	}
+
+	/// Get the number of coins that have ever been created.
+	pub fn coin_count() -> usize {
+		CoinShards::get()
+			.shard
+			.iter()
+			.map(|shard| shard.list.len())
+			.sum()
+	}
+
+	/// Get the number of encrypted notes that have been stored.
+	pub fn note_count() -> usize {
+		EncValueList::get().len()
+	}
+
+	/// Get the Merkle membership witness of the commitment `cm`.
+	/// Returns `None` if `cm` is not in the ledger, or if the hash parameters
+	/// do not match the checksum stored in the ledger.
+	pub fn coin_witness(cm: &[u8; 32]) -> Option<CoinWitness> {
+		let coin_shards = CoinShards::get();
+		let shard_index = cm[0];
+		let shard = &coin_shards.shard[shard_index as usize];
+		let leaf_index = shard.list.iter().position(|e| e == cm)?;
+
+		let hash_param = Self::ledger_hash_param().ok()?;
+		let path = T::LedgerHash::witness_path(&hash_param, &shard.list, leaf_index)?;
+
+		Some(CoinWitness {
+			shard_index,
+			leaf_index: leaf_index as u64,
+			path,
+			root: shard.root,
+		})
+	}
+
+	// Private functions
+
+	/// The number of void numbers and the number of notes in the ledger,
+	/// which the weights of the private calls grow with.
+	/// Every coin is stored with exactly one note, so the number of notes
+	/// is also the number of coins in the ledger.
+	fn ledger_sizes() -> (u32, u32) {
+		let void_numbers = VNList::decode_len().unwrap_or_default();
+		let notes = EncValueList::decode_len().unwrap_or_default();
+		(void_numbers as u32, notes as u32)
+	}
+
+	/// The largest number of coins in a shard when one of `cms` is added to it,
+	/// counting the earlier of `cms` in the same shard.
+	/// A ledger update rebuilds the Merkle tree of the shard of the new coin, and anyone can
+	/// pick commitments in the same shard, so the private calls are charged by the size of
+	/// the shards they update rather than by the average shard size.
+	fn shard_size(cms: &[[u8; 32]]) -> u32 {
+		let coin_shards = CoinShards::get();
+		cms.iter()
+			.enumerate()
+			.map(|(i, cm)| {
+				let earlier = cms[..i].iter().filter(|other| other[0] == cm[0]).count();
+				(coin_shards.shard[cm[0] as usize].list.len() + earlier) as u32
+			})
+			.max()
+			.unwrap_or_default()
+	}
+
+	/// The prepared transfer verification key, from the cache in the ledger.
+	/// If the cache is empty, or was prepared from another key than the one of the ledger,
+	/// the key of `Config::Verifier` is checked against the ledger, prepared and cached.
+	fn transfer_key() -> Result<Vec<u8>, DispatchError> {
+		let checksum = TransferZKPKeyChecksum::get();
+		if let Some((prepared_checksum, key)) = PreparedTransferKey::get() {
+			if prepared_checksum == checksum {
+				return Ok(key);
+			}
+		}
+		let key = Self::prepare_key(
+			checksum,
+			T::Verifier::transfer_key_checksum,
+			T::Verifier::prepare_transfer_key,
+		)?;
+		PreparedTransferKey::put((checksum, key.clone()));
+		Ok(key)
+	}
+
+	/// The prepared reclaim verification key, from the cache in the ledger.
+	/// If the cache is empty, or was prepared from another key than the one of the ledger,
+	/// the key of `Config::Verifier` is checked against the ledger, prepared and cached.
+	fn reclaim_key() -> Result<Vec<u8>, DispatchError> {
+		let checksum = ReclaimZKPKeyChecksum::get();
+		if let Some((prepared_checksum, key)) = PreparedReclaimKey::get() {
+			if prepared_checksum == checksum {
+				return Ok(key);
+			}
+		}
+		let key = Self::prepare_key(
+			checksum,
+			T::Verifier::reclaim_key_checksum,
+			T::Verifier::prepare_reclaim_key,
+		)?;
+		PreparedReclaimKey::put((checksum, key.clone()));
+		Ok(key)
+	}
+
+	/// Prepare a verification key of `Config::Verifier`,
+	/// after checking that it matches the `checksum` stored in the ledger.
+	fn prepare_key(
+		checksum: [u8; 32],
+		local_checksum: fn() -> Option<[u8; 32]>,
+		prepare: fn() -> Option<Vec<u8>>,
+	) -> Result<Vec<u8>, DispatchError> {
+		let checksum_local = local_checksum().ok_or(<Error<T>>::ZkpKeyChecksumFail)?;
+		ensure!(checksum_local == checksum, <Error<T>>::ZkpKeyMismatch);
+		prepare().ok_or_else(|| <Error<T>>::ZkpKeyPrepareFail.into())
+	}
+
+	/// Load the parameters of the ledger hash,
+	/// after checking that they match the checksum stored in the ledger.
+	fn ledger_hash_param() -> Result<<T::LedgerHash as LedgerHash>::Param, DispatchError> {
+		let hash_param_checksum_local =
+			T::LedgerHash::param_checksum().ok_or(<Error<T>>::HashParamChecksumFail)?;
+		ensure!(
+			hash_param_checksum_local == HashParamChecksum::get(),
+			<Error<T>>::HashParamMismatch
+		);
+		T::LedgerHash::param().ok_or_else(|| <Error<T>>::HashParamDesFail.into())
+	}
+
+	/// Load the commitment parameters of `Config::Verifier`,
+	/// after checking that they match the checksum stored in the ledger.
+	fn commit_param() -> Result<CommitmentParam, DispatchError> {
+		let commit_param_checksum_local =
+			T::Verifier::commit_param_checksum().ok_or(<Error<T>>::CommitParamChecksumFail)?;
+		ensure!(
+			commit_param_checksum_local == CommitParamChecksum::get(),
+			<Error<T>>::CommitParamMismatch
+		);
+		T::Verifier::commit_param().ok_or_else(|| <Error<T>>::CommitParamDesFail.into())
+	}
+
+	/// Fail with `OperationPaused` if `operation` is paused for `asset_id` or for all the assets.
+	fn ensure_not_paused(
+		operation: PausableOperation,
+		asset_id: AssetId,
+	) -> Result<(), DispatchError> {
+		ensure!(
+			!Paused::contains_key(operation, None::<AssetId>)
+				&& !Paused::contains_key(operation, Some(asset_id)),
+			<Error<T>>::OperationPaused
+		);
+		Ok(())
+	}
+
+	/// The headroom left under `limit` once the used amount rises from `used` to `new_used`,
+	/// if this is the rise that drops the headroom to the warning threshold,
+	/// see `HEADROOM_WARNING_DIVISOR`.
+	fn headroom_warning(
+		limit: AssetBalance,
+		used: AssetBalance,
+		new_used: AssetBalance,
+	) -> Option<AssetBalance> {
+		let threshold = limit / HEADROOM_WARNING_DIVISOR;
+		let headroom = limit.saturating_sub(new_used);
+		if headroom <= threshold && limit.saturating_sub(used) > threshold {
+			Some(headroom)
+		} else {
+			None
+		}
+	}
+
+	/// Record `amount` reclaimed at block `now` in the reclaim `history` of an asset.
+	/// The reclaims of a block are recorded together. Beyond `MAX_RECLAIM_HISTORY` blocks,
+	/// the oldest amount is moved to the next block, so it counts against the limit
+	/// for longer than `period`: the limit is enforced conservatively, never exceeded.
+	fn record_reclaim(
+		history: &mut Vec<(T::BlockNumber, AssetBalance)>,
+		now: T::BlockNumber,
+		amount: AssetBalance,
+	) {
+		match history.last_mut() {
+			Some((block, reclaimed)) if *block == now => {
+				*reclaimed = reclaimed.saturating_add(amount)
+			}
+			_ => history.push((now, amount)),
+		}
+		if history.len() > MAX_RECLAIM_HISTORY {
+			let (_, oldest) = history.remove(0);
+			history[0].1 = history[0].1.saturating_add(oldest);
+		}
+	}
+
+	/// Write the `balance` of `who` after a debit, removing the entry if it is zero.
+	/// A non-zero balance below the minimum balance of the asset is
+	/// burned or rejected, depending on the dust policy of the asset.
+	fn write_balance(
+		asset_id: AssetId,
+		who: &T::AccountId,
+		balance: AssetBalance,
+	) -> Result<(), DispatchError> {
+		if let Some((min_balance, dust_policy)) = MinBalances::get(asset_id) {
+			if !balance.is_zero() && balance < min_balance {
+				match dust_policy {
+					DustPolicy::Reject => return Err(<Error<T>>::BalanceBelowMinimum.into()),
+					DustPolicy::Burn => {
+						let total_supply = TotalSupply::get(asset_id)
+							.checked_sub(balance)
+							.ok_or(<Error<T>>::Underflow)?;
+						TotalSupply::insert(asset_id, total_supply);
+						<Balances<T>>::remove(asset_id, who);
+						Self::deposit_event(RawEvent::DustBurned(asset_id, who.clone(), balance));
+						return Ok(());
+					}
+				}
+			}
+		}
+
+		if balance.is_zero() {
+			<Balances<T>>::remove(asset_id, who);
+		} else {
+			<Balances<T>>::insert(asset_id, who, balance);
+		}
+		Ok(())
+	}
+
+	/// Write the `balance` of `who` after a credit.
+	/// The dust policy only applies to the debited side: a credit that leaves
+	/// `who` below the minimum balance of the asset is rejected.
+	fn write_credited_balance(
+		asset_id: AssetId,
+		who: &T::AccountId,
+		balance: AssetBalance,
+	) -> Result<(), DispatchError> {
+		if let Some((min_balance, _)) = MinBalances::get(asset_id) {
+			ensure!(
+				balance.is_zero() || balance >= min_balance,
+				<Error<T>>::BelowMinBalance
+			);
+		}
+		Self::write_balance(asset_id, who, balance)
+	}
+
+	/// Issue `total` units of the new asset `asset_id` to `owner`,
+	/// who has `deposit` reserved until the asset is destroyed.
+	fn do_issue(
+		asset_id: AssetId,
+		owner: T::AccountId,
+		total: AssetBalance,
+		deposit: BalanceOf<T>,
+	) -> Result<(), DispatchError> {
+		// if the asset_id has a total suply != 0, then this asset is initialized;
+		// an asset that is being destroyed still has its details
+		ensure!(
+			!TotalSupply::contains_key(&asset_id) && !<Asset<T>>::contains_key(&asset_id),
+			<Error<T>>::AlreadyInitialized
+		);
+
+		// the shielded ledger is shared by all the assets,
+		// it is only initialized when the first asset is created
+		if !LedgerInitialized::get() {
+			Self::init_ledger()?;
+		}
+
+		// initialize the asset with `total` number of supplies
+		// the total number of private asset (pool balance) remain 0
+		// the assets is credit to the owner's account
+		PoolBalance::insert(asset_id, 0);
+		TotalSupply::insert(asset_id, total);
+		Self::write_credited_balance(asset_id, &owner, total)?;
+		<Asset<T>>::insert(
+			asset_id,
+			AssetDetails {
+				owner: owner.clone(),
+				deposit,
+			},
+		);
+
+		// deposit the event after all the writes
+		Self::deposit_event(RawEvent::Issued(asset_id, owner, total));
+		Ok(())
+	}
+
+	/// Initialize the shielded ledger that is shared by all the assets:
+	/// store the checksums of the parameters and the ZKP verification keys,
+	/// and start with an empty coin list, void number list and note lists.
+	fn init_ledger() -> Result<(), DispatchError> {
+		// for now we hard code the parameters generated from the following seed:
+		//  * hash parameter seed: [1u8; 32]
+		//  * commitment parameter seed: [2u8; 32]
+		// We may want to pass those two in for `init`
+		// make sure the ledger hash parameters can be loaded before storing their checksum
+		T::LedgerHash::param().ok_or(<Error<T>>::HashParamDesFail)?;
+		let hash_param_checksum =
+			T::LedgerHash::param_checksum().ok_or(<Error<T>>::HashParamChecksumFail)?;
+
+		// likewise for the commitment parameters
+		T::Verifier::commit_param().ok_or(<Error<T>>::CommitParamDesFail)?;
+		let commit_param_checksum =
+			T::Verifier::commit_param_checksum().ok_or(<Error<T>>::CommitParamChecksumFail)?;
+
+		// push the checksums for ZKP verification keys to the ledger storage
+		//
+		// NOTE:
+		//    this is is generated via
+		//      let zkp_key = zkp::keys::manta_XXX_zkp_key_gen(&hash_param_seed, &commit_param_seed);
+		//
+		// for prototype, we use this function to generate the ZKP verification key
+		// for product we should use a MPC protocol to build the ZKP verification key
+		// and then deploy that vk
+		//
+		let transfer_key_digest =
+			T::Verifier::transfer_key_checksum().ok_or(<Error<T>>::ZkpKeyChecksumFail)?;
+		let reclaim_key_digest =
+			T::Verifier::reclaim_key_checksum().ok_or(<Error<T>>::ZkpKeyChecksumFail)?;
+
+		// prepare the keys once, when they are registered
+		let transfer_key =
+			T::Verifier::prepare_transfer_key().ok_or(<Error<T>>::ZkpKeyPrepareFail)?;
+		let reclaim_key =
+			T::Verifier::prepare_reclaim_key().ok_or(<Error<T>>::ZkpKeyPrepareFail)?;
+
+		// all the checksums are computed, update the storage
+		HashParamChecksum::put(hash_param_checksum);
+		CommitParamChecksum::put(commit_param_checksum);
+		TransferZKPKeyChecksum::put(transfer_key_digest);
+		ReclaimZKPKeyChecksum::put(reclaim_key_digest);
+		PreparedTransferKey::put((transfer_key_digest, transfer_key));
+		PreparedReclaimKey::put((reclaim_key_digest, reclaim_key));
+
+		// coin_shards are 256 lists of commitments
+		let coin_shards = MantaPrivateAssetLedger::default();
+		CoinShards::put(coin_shards);
+		VNList::put(Vec::<[u8; 32]>::new());
+		EncValueList::put(Vec::<MantaEciesCiphertext>::new());
+		LedgerInitialized::put(true);
+		StorageVersion::put(Releases::V2);
+
+		Ok(())
+	}
 }
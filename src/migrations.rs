@@ -0,0 +1,97 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The storage migrations of the pallet.
+//!
+//! `StorageVersion` records the layout of the storage. A chain that ran the first
+//! release of the pallet is at `Releases::V1`, and is moved to `Releases::V2`
+//! by `on_runtime_upgrade`. A ledger initialized by this release starts at `Releases::V2`.
+
+use crate::*;
+use frame_support::storage::migration::storage_iter;
+
+/// The layout of the storage of the pallet.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+	/// The first release, where the ledger was reset by every `init_asset`.
+	V1,
+	/// The ledger is initialized once, and `LedgerInitialized` is set.
+	/// `Balances` is keyed by asset first, instead of by account first.
+	V2,
+}
+
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V1
+	}
+}
+
+/// Move the storage from `Releases::V1` to `Releases::V2`.
+/// Returns the weight of the migration, which is 0 if the storage is already migrated.
+/// The first release did not record who issued an asset, so the assets issued before
+/// the migration get no `Asset` details: they cannot be destroyed with `destroy_asset`.
+pub(crate) fn migrate_to_v2<T: Config>() -> Weight {
+	if StorageVersion::get() != Releases::V1 {
+		return 0;
+	}
+	let mut weight = T::DbWeight::get().reads(1);
+
+	weight = weight.saturating_add(ledger_initialized::<T>());
+	weight = weight.saturating_add(balances_by_asset::<T>());
+
+	StorageVersion::put(Releases::V2);
+	weight.saturating_add(T::DbWeight::get().writes(1))
+}
+
+/// Set `LedgerInitialized` on a chain whose ledger was initialized by `init_asset`,
+/// so that the next asset does not reset the void numbers and the notes.
+fn ledger_initialized<T: Config>() -> Weight {
+	if !HashParamChecksum::exists() {
+		return T::DbWeight::get().reads(1);
+	}
+	LedgerInitialized::put(true);
+	T::DbWeight::get().reads_writes(1, 1)
+}
+
+/// Re-key `Balances` by asset first.
+/// The first release keyed it by account first, with the same hashers.
+fn balances_by_asset<T: Config>() -> Weight {
+	// a key is `blake2_128(account) ++ account ++ blake2_128(asset_id) ++ asset_id`
+	let decode_key = |key: &[u8]| -> Option<(T::AccountId, AssetId)> {
+		let mut key = key.get(16..)?;
+		let who = T::AccountId::decode(&mut key).ok()?;
+		let mut key = key.get(16..)?;
+		let asset_id = AssetId::decode(&mut key).ok()?;
+		Some((who, asset_id))
+	};
+
+	// drain the old keys before inserting the new ones under the same prefix
+	let balances: Vec<(Vec<u8>, AssetBalance)> =
+		storage_iter::<AssetBalance>(b"Assets", b"Balances")
+			.drain()
+			.collect();
+	let count = balances.len() as Weight;
+	for (key, balance) in balances {
+		match decode_key(&key) {
+			Some((who, asset_id)) => <Balances<T>>::insert(asset_id, who, balance),
+			None => {
+				log::error!(target: "manta-pay", "failed to migrate the balance with key: {:?}", key)
+			}
+		}
+	}
+
+	T::DbWeight::get().reads_writes(count, count.saturating_mul(2))
+}
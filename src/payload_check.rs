@@ -0,0 +1,204 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Strict checks of the curve points carried by the payloads.
+//!
+//! The payloads are deserialized into byte arrays, and the pallet stores and compares
+//! those bytes, so two encodings of the same point would be two different coins.
+//! Every curve point is checked to be
+//! * encoded with canonical field elements and flags,
+//! * on the curve,
+//! * in the prime order subgroup,
+//! * encoded exactly as it serializes back.
+//!
+//! The commitments `k` and `cm` and the ledger roots are Jubjub points.
+//! The elements of a Groth16 proof are BLS12-381 points,
+//! and none of them can be the identity.
+//! The void numbers are PRF outputs rather than points, so they are not checked here.
+//!
+//! The ephemeral public keys of the ECIES notes, including the notes for the auditors,
+//! are X25519 u-coordinates. They are checked to be canonical field elements, and not
+//! to be one of the points of small order, whose shared secret does not depend on the
+//! recipient key.
+
+use ark_bls12_381::{G1Affine, G2Affine};
+use ark_ec::AffineCurve;
+use ark_ff::{FpParameters, PrimeField, Zero};
+use ark_serialize::{
+	CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize, EdwardsFlags, Flags,
+	SWFlags,
+};
+use ark_std::vec::Vec;
+use manta_crypto::{CommitmentOutput, MantaEciesCiphertext};
+use manta_data::*;
+
+/// Size, in bytes, of a compressed BLS12-381 G1 point.
+const G1_SIZE: usize = 48;
+
+/// Size, in bytes, of a compressed BLS12-381 G2 point.
+const G2_SIZE: usize = 96;
+
+/// The canonical encodings of the X25519 points of small order.
+const X25519_SMALL_ORDER: [[u8; 32]; 5] = [
+	// 0
+	[0x00; 32],
+	// 1
+	[
+		0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+		0x00, 0x00,
+	],
+	// a point of order 8
+	[
+		0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+		0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+		0xb8, 0x00,
+	],
+	// a point of order 8
+	[
+		0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+		0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+		0x11, 0x57,
+	],
+	// p - 1
+	[
+		0xec, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+		0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+		0xff, 0x7f,
+	],
+];
+
+/// The reasons a payload is rejected by the checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadError {
+	/// A coordinate is not a canonical field element, or its flags are invalid
+	NonCanonicalFieldElement,
+	/// A point is not on the curve
+	PointNotOnCurve,
+	/// A point is not in the prime order subgroup
+	PointNotInSubgroup,
+	/// A point is not encoded the way it serializes
+	NonCanonicalPoint,
+	/// A proof is not encoded the way it serializes, or has an identity element
+	MalleatedProof,
+}
+
+/// Check the commitments of a mint.
+pub fn check_mint(data: &MintData) -> Result<(), PayloadError> {
+	check_jubjub(&data.cm)?;
+	check_jubjub(&data.k)?;
+	check_note(&data.encrypted_note)
+}
+
+/// Check the commitments, the ledger roots and the proof of a private transfer.
+pub fn check_transfer(data: &PrivateTransferData) -> Result<(), PayloadError> {
+	for sender in [&data.sender_1, &data.sender_2].iter() {
+		check_jubjub(&sender.k)?;
+		check_jubjub(&sender.root)?;
+	}
+	for receiver in [&data.receiver_1, &data.receiver_2].iter() {
+		check_jubjub(&receiver.k)?;
+		check_jubjub(&receiver.cm)?;
+		check_note(&receiver.encrypted_note)?;
+	}
+	check_proof(&data.proof)
+}
+
+/// Check the commitments, the ledger roots and the proof of a reclaim.
+pub fn check_reclaim(data: &ReclaimData) -> Result<(), PayloadError> {
+	for sender in [&data.sender_1, &data.sender_2].iter() {
+		check_jubjub(&sender.k)?;
+		check_jubjub(&sender.root)?;
+	}
+	check_jubjub(&data.receiver.k)?;
+	check_jubjub(&data.receiver.cm)?;
+	check_note(&data.receiver.encrypted_note)?;
+	check_proof(&data.proof)
+}
+
+/// Check the ephemeral public key of an ECIES note, such as a note for an auditor.
+pub fn check_note(note: &MantaEciesCiphertext) -> Result<(), PayloadError> {
+	let u = &note.ephemeral_pk;
+
+	// the top bit is not part of the coordinate, and the coordinate is below p = 2^255 - 19
+	if u[31] & 0x80 != 0 || (u[31] == 0x7f && u[1..31].iter().all(|b| *b == 0xff) && u[0] >= 0xed) {
+		return Err(PayloadError::NonCanonicalFieldElement);
+	}
+
+	if X25519_SMALL_ORDER.contains(u) {
+		return Err(PayloadError::PointNotInSubgroup);
+	}
+	Ok(())
+}
+
+/// Check a compressed Groth16 proof `(A, B, C)`.
+fn check_proof(proof: &[u8]) -> Result<(), PayloadError> {
+	if proof.len() != 2 * G1_SIZE + G2_SIZE {
+		return Err(PayloadError::MalleatedProof);
+	}
+	let (a, rest) = proof.split_at(G1_SIZE);
+	let (b, c) = rest.split_at(G2_SIZE);
+
+	let a = check_point::<G1Affine, SWFlags>(a).map_err(malleated)?;
+	let b = check_point::<G2Affine, SWFlags>(b).map_err(malleated)?;
+	let c = check_point::<G1Affine, SWFlags>(c).map_err(malleated)?;
+	if a.is_zero() || b.is_zero() || c.is_zero() {
+		return Err(PayloadError::MalleatedProof);
+	}
+	Ok(())
+}
+
+/// A proof point that does not serialize back to its bytes makes a malleated proof.
+fn malleated(e: PayloadError) -> PayloadError {
+	match e {
+		PayloadError::NonCanonicalPoint => PayloadError::MalleatedProof,
+		e => e,
+	}
+}
+
+/// Check a compressed Jubjub point.
+fn check_jubjub(bytes: &[u8]) -> Result<(), PayloadError> {
+	check_point::<CommitmentOutput, EdwardsFlags>(bytes).map(|_| ())
+}
+
+/// Check that `bytes` is the canonical encoding of a point of the prime order subgroup of `G`,
+/// whose compressed encoding carries flags `F`.
+fn check_point<G, F>(bytes: &[u8]) -> Result<G, PayloadError>
+where
+	G: AffineCurve,
+	F: Flags,
+{
+	// the coordinate must be below the modulus, with valid flags
+	<G::BaseField as CanonicalDeserializeWithFlags>::deserialize_with_flags::<_, F>(bytes)
+		.map_err(|_| PayloadError::NonCanonicalFieldElement)?;
+
+	// `deserialize_unchecked` only recovers the point from the coordinate
+	let point = G::deserialize_unchecked(bytes).map_err(|_| PayloadError::PointNotOnCurve)?;
+
+	let modulus = <<G::ScalarField as PrimeField>::Params as FpParameters>::MODULUS;
+	if !point.mul(modulus).is_zero() {
+		return Err(PayloadError::PointNotInSubgroup);
+	}
+
+	let mut canonical = Vec::with_capacity(bytes.len());
+	point
+		.serialize(&mut canonical)
+		.map_err(|_| PayloadError::NonCanonicalPoint)?;
+	if canonical[..] != bytes[..] {
+		return Err(PayloadError::NonCanonicalPoint);
+	}
+	Ok(point)
+}
@@ -102,3 +102,15 @@ pub const TEST_RECLAIM_PAYLOAD: [u8; RECLAIM_PAYLOAD_SIZE] = [
 	122, 47, 116, 253, 91, 61, 88, 179, 82, 15, 104, 136, 173, 160, 37, 155, 83, 43, 104, 120, 255,
 	243, 69, 186, 134, 232, 210, 20, 142, 159, 244, 212, 169, 137, 47, 15,
 ];
+
+/// The transfers of the `batch_private_transfer` benchmark, each with the mints of the two
+/// coins it spends. The transfers are proven against the ledger with the coins of all the mints.
+/// Only the transfer of `TEST_MINT_10_PAYLOAD` and `TEST_MINT_11_PAYLOAD` is precomputed so far;
+/// `print_batch_payloads` in the tests prints the list for batches of up to `MAX_BATCH_SIZE`.
+pub const TEST_BATCH: &[(
+	[[u8; MINT_PAYLOAD_SIZE]; 2],
+	[u8; PRIVATE_TRANSFER_PAYLOAD_SIZE],
+)] = &[(
+	[TEST_MINT_10_PAYLOAD, TEST_MINT_11_PAYLOAD],
+	TEST_TRANSFER_PAYLOAD,
+)];
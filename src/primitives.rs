@@ -0,0 +1,127 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The pairing check and the coin ledger update used by the pallet.
+//!
+//! With the `native-crypto` feature, both are computed by the host functions of
+//! `manta-pay-host`; otherwise they are computed inside the runtime.
+//! The `runtime_*` functions always compute inside the runtime.
+
+use ark_bls12_381::{Bls12_381, G1Affine, G2Affine};
+use ark_ec::PairingEngine;
+use ark_ff::One;
+#[cfg(feature = "native-crypto")]
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+#[cfg(feature = "native-crypto")]
+use codec::{Decode, Encode};
+use manta_crypto::*;
+use manta_ledger::{LedgerSharding, MantaPrivateAssetLedger};
+
+/// Check that the product of the pairings of `pairs` is one.
+#[cfg(feature = "native-crypto")]
+pub fn pairing_check(pairs: &[(G1Affine, G2Affine)]) -> bool {
+	let mut encoded = Vec::with_capacity(pairs.len());
+	for (g1, g2) in pairs {
+		let mut g1_bytes = Vec::new();
+		let mut g2_bytes = Vec::new();
+		if g1.serialize(&mut g1_bytes).is_err() || g2.serialize(&mut g2_bytes).is_err() {
+			return false;
+		}
+		encoded.push((g1_bytes, g2_bytes));
+	}
+	manta_pay_host::manta_crypto::pairing_check(encoded)
+}
+
+/// Check that the product of the pairings of `pairs` is one.
+#[cfg(not(feature = "native-crypto"))]
+pub fn pairing_check(pairs: &[(G1Affine, G2Affine)]) -> bool {
+	runtime_pairing_check(pairs)
+}
+
+/// Check that the product of the pairings of `pairs` is one, inside the runtime.
+pub fn runtime_pairing_check(pairs: &[(G1Affine, G2Affine)]) -> bool {
+	let prepared: Vec<(
+		<Bls12_381 as PairingEngine>::G1Prepared,
+		<Bls12_381 as PairingEngine>::G2Prepared,
+	)> = pairs
+		.iter()
+		.map(|(g1, g2)| ((*g1).into(), (*g2).into()))
+		.collect();
+	Bls12_381::product_of_pairings(&prepared).is_one()
+}
+
+/// Add `cm` to its shard of `ledger`, and update the root of the shard.
+/// Only the shard of `cm` and the checksum of `param` are sent to the host function,
+/// which hashes with its own copy of the ledger hash parameters, and fails if the copy
+/// does not have the same checksum.
+/// Returns `false` if the shard could not be updated.
+#[cfg(feature = "native-crypto")]
+pub fn ledger_update(
+	ledger: &mut MantaPrivateAssetLedger,
+	cm: &[u8; 32],
+	param: &HashParam,
+) -> bool {
+	let param_checksum = match param.get_checksum() {
+		Ok(param_checksum) => param_checksum,
+		Err(e) => {
+			log::error!(target: "manta-pay", "failed to compute the hash param checksum with error: {:?}", e);
+			return false;
+		}
+	};
+	let shard_index = cm[0] as usize;
+	let updated = manta_pay_host::manta_crypto::ledger_update(
+		ledger.shard[shard_index].encode(),
+		*cm,
+		param_checksum,
+	)
+	.and_then(|encoded| Decode::decode(&mut encoded.as_slice()).ok());
+	match updated {
+		Some(shard) => {
+			ledger.shard[shard_index] = shard;
+			true
+		}
+		None => {
+			log::error!(target: "manta-pay", "failed to update the ledger through the host function");
+			false
+		}
+	}
+}
+
+/// Add `cm` to its shard of `ledger`, and update the root of the shard.
+/// Returns `false` if the shard could not be updated.
+#[cfg(not(feature = "native-crypto"))]
+pub fn ledger_update(
+	ledger: &mut MantaPrivateAssetLedger,
+	cm: &[u8; 32],
+	param: &HashParam,
+) -> bool {
+	runtime_ledger_update(ledger, cm, param)
+}
+
+/// Add `cm` to its shard of `ledger`, and update the root of the shard, inside the runtime.
+pub fn runtime_ledger_update(
+	ledger: &mut MantaPrivateAssetLedger,
+	cm: &[u8; 32],
+	param: &HashParam,
+) -> bool {
+	ledger
+		.update(cm, param.clone())
+		.map_err(|e| {
+			log::error!(target: "manta-pay", "failed to update the ledger with error: {:?}", e);
+		})
+		.is_ok()
+}
@@ -0,0 +1,27 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the manta-pay pallet.
+
+use crate::CoinWitness;
+
+sp_api::decl_runtime_apis! {
+	/// The API to query the private ledger of manta-pay.
+	pub trait MantaPayApi {
+		/// Get the Merkle membership witness of the commitment `cm`.
+		fn coin_witness(cm: [u8; 32]) -> Option<CoinWitness>;
+	}
+}
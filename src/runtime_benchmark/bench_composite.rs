@@ -41,6 +41,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		PalletBalances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		MantaModule: pallet_manta_pay::{Module, Call, Storage, Event<T>},
 	}
 );
@@ -66,7 +67,7 @@ impl frame_system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type DbWeight = ();
 	type Version = ();
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -76,8 +77,29 @@ impl frame_system::Config for Test {
 	type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const AssetDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
 impl Config for Test {
 	type Event = ();
+	type Currency = PalletBalances;
+	type AssetDeposit = AssetDeposit;
+	type PauseOrigin = frame_system::EnsureRoot<u64>;
+	type CreateOrigin = frame_system::EnsureSigned<u64>;
+	type Verifier = Groth16;
+	type LedgerHash = Pedersen;
 	type WeightInfo = ();
 }
 
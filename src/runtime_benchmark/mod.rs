@@ -25,9 +25,14 @@ use ark_std::{boxed::Box, primitive::str, vec, vec::Vec};
 use frame_benchmarking::{account, benchmarks, whitelisted_caller};
 use frame_system::{EventRecord, RawOrigin};
 use manta_asset::TEST_ASSET;
+use sp_runtime::traits::Bounded;
 
 const SEED: u32 = 0;
 
+/// The largest number of void numbers, notes and coins the ledger is grown by
+/// before benchmarking the private calls.
+const MAX_LEDGER_SIZE: u32 = 1_024;
+
 pub fn assert_last_event<T: Config>(generic_event: <T as Config>::Event) {
 	let events = frame_system::Module::<T>::events();
 	let system_event: <T as frame_system::Config>::Event = generic_event.into();
@@ -35,22 +40,118 @@ pub fn assert_last_event<T: Config>(generic_event: <T as Config>::Event) {
 	assert_eq!(event, &system_event);
 }
 
+/// Grow the ledger by `void_numbers` void numbers and `notes` notes, and fill each of
+/// `target_shards` with `shard_size` coins.
+/// A ledger update rebuilds the Merkle tree of the shard of the new coin, so the private calls
+/// charge `c` for the size of the shards they add coins to. The rest of the ledger is read and
+/// written whole, which `n` charges for: every coin is stored with exactly one note, so one coin
+/// is added for each note, spread over the other shards.
+/// The shards in `skipped_shards` are left as they are, even when targeted: the roots of the
+/// shards of the spent coins must stay the ones the precomputed proofs are built on.
+fn populate_ledger<T: Config>(
+	void_numbers: u32,
+	notes: u32,
+	shard_size: u32,
+	target_shards: &[u8],
+	skipped_shards: &[u8],
+) {
+	let mut vn_list = VNList::get();
+	vn_list.extend((0..void_numbers).map(|i| filler_bytes(0xff, i)));
+	VNList::put(vn_list);
+
+	let note = MintData::deserialize(precomputed_coins::TEST_MINT_10_PAYLOAD.as_ref())
+		.unwrap()
+		.encrypted_note;
+	EncValueList::mutate(|list| list.extend(sp_std::iter::repeat(note).take(notes as usize)));
+
+	let hash_param = T::LedgerHash::param().unwrap();
+	let mut coin_shards = CoinShards::get();
+	let mut shards = (0..=u8::MAX)
+		.filter(|shard| !skipped_shards.contains(shard) && !target_shards.contains(shard))
+		.cycle();
+	for i in 0..notes {
+		let mut cm = filler_bytes(0xfe, i);
+		cm[0] = shards.next().unwrap();
+		assert!(T::LedgerHash::update(&mut coin_shards, &cm, &hash_param));
+	}
+	let mut target_shards = target_shards.to_vec();
+	target_shards.sort_unstable();
+	target_shards.dedup();
+	for shard in target_shards
+		.into_iter()
+		.filter(|shard| !skipped_shards.contains(shard))
+	{
+		let mut cms = (0..shard_size).map(|i| {
+			let mut cm = filler_bytes(0xfd, i);
+			cm[0] = shard;
+			cm
+		});
+		// an update rebuilds the whole shard, so only the last coin goes through one
+		if let Some(last) = cms.next_back() {
+			coin_shards.shard[shard as usize].list.extend(cms);
+			assert!(T::LedgerHash::update(&mut coin_shards, &last, &hash_param));
+		}
+	}
+	CoinShards::put(coin_shards);
+}
+
+/// The shards of the coins of the precomputed mints.
+fn minted_shards() -> Vec<u8> {
+	shards_of(&[
+		precomputed_coins::TEST_MINT_10_PAYLOAD,
+		precomputed_coins::TEST_MINT_11_PAYLOAD,
+	])
+}
+
+/// The shards of the coins of `mints`.
+fn shards_of(mints: &[MintPayload]) -> Vec<u8> {
+	mints
+		.iter()
+		.map(|payload| MintData::deserialize(payload.as_ref()).unwrap().cm[0])
+		.collect()
+}
+
+/// 32 bytes of `tag`, with the index `i` in bytes 1 to 4.
+fn filler_bytes(tag: u8, i: u32) -> [u8; 32] {
+	let mut bytes = [tag; 32];
+	bytes[1..5].copy_from_slice(&i.to_le_bytes());
+	bytes
+}
+
 benchmarks! {
 
 	init_asset {
-		let caller: T::AccountId = whitelisted_caller();
+		let origin = T::CreateOrigin::successful_origin();
+		let owner = T::CreateOrigin::ensure_origin(origin.clone())
+			.map_err(|_| "invalid create origin")?;
 		let total = 1000u128;
-	}: init_asset (RawOrigin::Signed(caller.clone()), TEST_ASSET, total)
+	}: init_asset<T::Origin> (origin, TEST_ASSET, total)
 	verify {
-		assert_last_event::<T>(RawEvent::Issued(TEST_ASSET, caller.clone(), total).into());
+		assert_last_event::<T>(RawEvent::Issued(TEST_ASSET, owner, total).into());
 		assert_eq!(<TotalSupply>::get(TEST_ASSET), total);
 	}
 
+	create_asset {
+		let origin = T::CreateOrigin::successful_origin();
+		let owner = T::CreateOrigin::ensure_origin(origin.clone())
+			.map_err(|_| "invalid create origin")?;
+		T::Currency::make_free_balance_be(&owner, BalanceOf::<T>::max_value());
+		let total = 1000u128;
+		// the worst case: all the ids but the last one searched are taken
+		let asset_id = (MAX_ASSET_ID_SEARCH - 1) as AssetId;
+		for taken in 0..asset_id {
+			TotalSupply::insert(taken, total);
+		}
+	}: create_asset<T::Origin> (origin, total)
+	verify {
+		assert_last_event::<T>(RawEvent::Issued(asset_id, owner.clone(), total).into());
+		assert_eq!(<Asset<T>>::get(asset_id).map(|details| details.owner), Some(owner));
+	}
+
 	transfer_asset {
 		let caller: T::AccountId = whitelisted_caller();
-		let origin: T::Origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-		<Balances<T>>::insert(&caller, TEST_ASSET, 1_000);
-		assert!(Module::<T>::init_asset(origin, TEST_ASSET, 1_000).is_ok());
+		<Balances<T>>::insert(TEST_ASSET, &caller, 1_000);
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1_000).is_ok());
 		let recipient: T::AccountId = account("recipient", 0, SEED);
 		let recipient_lookup: <T::Lookup as StaticLookup>::Source = T::Lookup::unlookup(recipient.clone());
 		let transfer_amount = 10;
@@ -63,18 +164,27 @@ benchmarks! {
 		assert_last_event::<T>(
 			RawEvent::Transferred(TEST_ASSET, caller.clone(), recipient.clone(), transfer_amount).into()
 		);
-		assert_eq!(Balances::<T>::get(&recipient, TEST_ASSET), transfer_amount);
+		assert_eq!(Balances::<T>::get(TEST_ASSET, &recipient), transfer_amount);
 	}
 
 
 	mint_private_asset {
+		let n in 0 .. MAX_LEDGER_SIZE;
+		let c in 0 .. MAX_LEDGER_SIZE;
 		let caller: T::AccountId = whitelisted_caller();
-		let origin: T::Origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-		<Balances<T>>::insert(&caller, TEST_ASSET, 1000);
-		assert!(Module::<T>::init_asset(origin, TEST_ASSET, 1000).is_ok());
+		<Balances<T>>::insert(TEST_ASSET, &caller, 1000);
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
+		let target = MintData::deserialize(precomputed_coins::TEST_MINT_10_PAYLOAD.as_ref())
+			.unwrap()
+			.cm[0];
+		populate_ledger::<T>(0, n, c, &[target], &[]);
+		// the view tag is stored as given, so its value does not change the cost
+		// and the tag does not need to match the note
 	}: mint_private_asset (
 		RawOrigin::Signed(caller),
-		precomputed_coins::TEST_MINT_10_PAYLOAD)
+		precomputed_coins::TEST_MINT_10_PAYLOAD,
+		Some(0),
+		None)
 	verify {
 		assert_eq!(TotalSupply::get(TEST_ASSET), 1000);
 		assert_eq!(PoolBalance::get(TEST_ASSET), 10);
@@ -82,44 +192,213 @@ benchmarks! {
 
 
 	private_transfer {
+		let v in 0 .. MAX_LEDGER_SIZE;
+		let n in 0 .. MAX_LEDGER_SIZE;
+		let c in 0 .. MAX_LEDGER_SIZE;
 		let caller: T::AccountId = whitelisted_caller();
 		let origin: T::Origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-		<Balances<T>>::insert(&caller, TEST_ASSET, 1000);
-		assert!(Module::<T>::init_asset(origin.clone(), TEST_ASSET, 1000).is_ok());
+		<Balances<T>>::insert(TEST_ASSET, &caller, 1000);
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
 
-		Module::<T>::mint_private_asset(origin.clone(), precomputed_coins::TEST_MINT_10_PAYLOAD).unwrap();
-		Module::<T>::mint_private_asset(origin, precomputed_coins::TEST_MINT_11_PAYLOAD).unwrap();
+		Module::<T>::mint_private_asset(origin.clone(), precomputed_coins::TEST_MINT_10_PAYLOAD, None, None).unwrap();
+		Module::<T>::mint_private_asset(origin, precomputed_coins::TEST_MINT_11_PAYLOAD, None, None).unwrap();
+		let data = PrivateTransferData::deserialize(
+			T::Verifier::benchmark_transfer_payload().as_ref()
+		).unwrap();
+		let targets = [data.receiver_1.cm[0], data.receiver_2.cm[0]];
+		populate_ledger::<T>(v, n, c, &targets, &minted_shards());
+		// `init_asset` stored the prepared key, so the call reads and decodes it from the ledger
+		assert!(PreparedTransferKey::exists());
+		// the view tags are stored as given, so their values do not change the cost
 	}: private_transfer (
 		RawOrigin::Signed(caller.clone()),
-		precomputed_coins::TEST_TRANSFER_PAYLOAD)
+		T::Verifier::benchmark_transfer_payload(),
+		[Some(0), Some(1)],
+		[None, None],
+		[Some(vec![0u8; MAX_MEMO_SIZE]), Some(vec![0u8; MAX_MEMO_SIZE])])
 	verify {
-		assert_last_event::<T>(RawEvent::PrivateTransferred(caller.clone()).into());
+		let data = PrivateTransferData::deserialize(
+			T::Verifier::benchmark_transfer_payload().as_ref()
+		).unwrap();
+		assert_last_event::<T>(
+			RawEvent::PrivateTransferred(
+				caller.clone(),
+				[data.sender_1.void_number, data.sender_2.void_number],
+				[data.receiver_1.cm, data.receiver_2.cm],
+				[data.receiver_1.cm[0], data.receiver_2.cm[0]],
+				[n as u64 + 2, n as u64 + 3],
+			).into()
+		);
 		assert_eq!(TotalSupply::get(TEST_ASSET), 1000);
 		assert_eq!(PoolBalance::get(TEST_ASSET), 21);
 	}
 
+	// `b` goes up to the number of precomputed transfers, see `precomputed_coins::TEST_BATCH`;
+	// all the mints are applied, so the proofs match the ledger whatever `b`
+	batch_private_transfer {
+		let b in 1 .. T::Verifier::benchmark_batch().len() as u32;
+		let v in 0 .. MAX_LEDGER_SIZE;
+		let n in 0 .. MAX_LEDGER_SIZE;
+		let c in 0 .. MAX_LEDGER_SIZE;
+		let caller: T::AccountId = whitelisted_caller();
+		let origin: T::Origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
+		<Balances<T>>::insert(TEST_ASSET, &caller, 1000);
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
+
+		let batch = T::Verifier::benchmark_batch();
+		let mints: Vec<MintPayload> = batch.iter().flat_map(|(mints, _)| mints.iter().copied()).collect();
+		for mint in mints.iter() {
+			Module::<T>::mint_private_asset(origin.clone(), *mint, None, None).unwrap();
+		}
+		let targets: Vec<u8> = batch
+			.iter()
+			.take(b as usize)
+			.flat_map(|(_, payload)| {
+				let data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+				vec![data.receiver_1.cm[0], data.receiver_2.cm[0]]
+			})
+			.collect();
+		populate_ledger::<T>(v, n, c, &targets, &shards_of(&mints));
+		// the same tags and memos as in the `private_transfer` benchmark
+		let transfers: Vec<BatchedTransfer> = batch
+			.iter()
+			.take(b as usize)
+			.map(|(_, payload)| BatchedTransfer {
+				payload: *payload,
+				view_tags: [Some(0), Some(1)],
+				auditor_notes: [None, None],
+				memos: [Some(vec![0u8; MAX_MEMO_SIZE]), Some(vec![0u8; MAX_MEMO_SIZE])],
+			})
+			.collect();
+	}: batch_private_transfer (
+		RawOrigin::Signed(caller.clone()),
+		transfers)
+	verify {
+		let data = PrivateTransferData::deserialize(batch[b as usize - 1].1.as_ref()).unwrap();
+		let note_index = n as u64 + mints.len() as u64 + 2 * (b as u64 - 1);
+		assert_last_event::<T>(
+			RawEvent::PrivateTransferred(
+				caller.clone(),
+				[data.sender_1.void_number, data.sender_2.void_number],
+				[data.receiver_1.cm, data.receiver_2.cm],
+				[data.receiver_1.cm[0], data.receiver_2.cm[0]],
+				[note_index, note_index + 1],
+			).into()
+		);
+	}
+
 	reclaim {
+		let v in 0 .. MAX_LEDGER_SIZE;
+		let n in 0 .. MAX_LEDGER_SIZE;
+		let c in 0 .. MAX_LEDGER_SIZE;
 		let caller: T::AccountId = whitelisted_caller();
 		let origin: T::Origin = T::Origin::from(RawOrigin::Signed(caller.clone()));
-		<Balances<T>>::insert(&caller, TEST_ASSET, 1000);
-		assert!(Module::<T>::init_asset(origin.clone(), TEST_ASSET, 1000).is_ok());
+		<Balances<T>>::insert(TEST_ASSET, &caller, 1000);
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
 
-		Module::<T>::mint_private_asset(origin.clone(), precomputed_coins::TEST_MINT_10_PAYLOAD).unwrap();
-		Module::<T>::mint_private_asset(origin, precomputed_coins::TEST_MINT_11_PAYLOAD).unwrap();
+		Module::<T>::mint_private_asset(origin.clone(), precomputed_coins::TEST_MINT_10_PAYLOAD, None, None).unwrap();
+		Module::<T>::mint_private_asset(origin, precomputed_coins::TEST_MINT_11_PAYLOAD, None, None).unwrap();
+		let data = ReclaimData::deserialize(
+			T::Verifier::benchmark_reclaim_payload().as_ref()
+		).unwrap();
+		populate_ledger::<T>(v, n, c, &[data.receiver.cm[0]], &minted_shards());
+		// `init_asset` stored the prepared key, so the call reads and decodes it from the ledger
+		assert!(PreparedReclaimKey::exists());
 
 		// pre-computed reclaimed circuit for a receiver of 10 assets
 		let reclaim_value = 11;
-
+		// the view tag is stored as given, so its value does not change the cost
 	}: reclaim (
 		RawOrigin::Signed(caller.clone()),
-		precomputed_coins::TEST_RECLAIM_PAYLOAD)
+		T::Verifier::benchmark_reclaim_payload(),
+		Some(0),
+		None,
+		Some(vec![0u8; MAX_MEMO_SIZE]))
 	verify {
+		let data = ReclaimData::deserialize(
+			T::Verifier::benchmark_reclaim_payload().as_ref()
+		).unwrap();
 		assert_last_event::<T>(
-			RawEvent::PrivateReclaimed(TEST_ASSET, caller.clone(), reclaim_value).into()
+			RawEvent::PrivateReclaimed(
+				TEST_ASSET,
+				caller.clone(),
+				reclaim_value,
+				[data.sender_1.void_number, data.sender_2.void_number],
+				data.receiver.cm,
+				data.receiver.cm[0],
+				n as u64 + 2,
+			).into()
 		);
 		assert_eq!(TotalSupply::get(TEST_ASSET), 1000);
 		assert_eq!(PoolBalance::get(TEST_ASSET), 10);
 	}
+
+	set_auditor_key {
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
+		let auditor_key = [1u8; 32];
+	}: set_auditor_key (RawOrigin::Root, TEST_ASSET, Some(auditor_key))
+	verify {
+		assert_last_event::<T>(RawEvent::AuditorKeySet(TEST_ASSET, Some(auditor_key)).into());
+		assert_eq!(AuditorKeys::get(TEST_ASSET), Some(auditor_key));
+	}
+
+	set_min_balance {
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
+	}: set_min_balance (RawOrigin::Root, TEST_ASSET, 10, DustPolicy::Burn)
+	verify {
+		assert_last_event::<T>(RawEvent::MinBalanceSet(TEST_ASSET, 10, DustPolicy::Burn).into());
+		assert_eq!(MinBalances::get(TEST_ASSET), Some((10, DustPolicy::Burn)));
+	}
+
+	set_pool_cap {
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
+	}: set_pool_cap (RawOrigin::Root, TEST_ASSET, Some(100))
+	verify {
+		assert_last_event::<T>(RawEvent::PoolCapSet(TEST_ASSET, Some(100)).into());
+		assert_eq!(PoolCaps::get(TEST_ASSET), Some(100));
+	}
+
+	set_reclaim_limit {
+		assert!(Module::<T>::init_asset(T::CreateOrigin::successful_origin(), TEST_ASSET, 1000).is_ok());
+		let reclaim_limit = ReclaimLimit { amount: 100, period: 10u32.into() };
+	}: set_reclaim_limit (RawOrigin::Root, TEST_ASSET, Some(reclaim_limit.clone()))
+	verify {
+		assert_last_event::<T>(RawEvent::ReclaimLimitSet(TEST_ASSET, Some(reclaim_limit.clone())).into());
+		assert_eq!(<ReclaimLimits<T>>::get(TEST_ASSET), Some(reclaim_limit));
+	}
+
+	pause {
+		let origin = T::PauseOrigin::successful_origin();
+	}: pause<T::Origin> (origin, PausableOperation::Reclaim, Some(TEST_ASSET))
+	verify {
+		assert_last_event::<T>(RawEvent::Paused(PausableOperation::Reclaim, Some(TEST_ASSET)).into());
+		assert!(Paused::contains_key(PausableOperation::Reclaim, Some(TEST_ASSET)));
+	}
+
+	unpause {
+		let origin = T::PauseOrigin::successful_origin();
+		Paused::insert(PausableOperation::Reclaim, Some(TEST_ASSET), ());
+	}: unpause<T::Origin> (origin, PausableOperation::Reclaim, Some(TEST_ASSET))
+	verify {
+		assert_last_event::<T>(RawEvent::Unpaused(PausableOperation::Reclaim, Some(TEST_ASSET)).into());
+		assert!(!Paused::contains_key(PausableOperation::Reclaim, Some(TEST_ASSET)));
+	}
+
+	destroy_asset {
+		let a in 1 .. 1_000;
+		let caller: T::AccountId = whitelisted_caller();
+		<Asset<T>>::insert(TEST_ASSET, AssetDetails { owner: caller.clone(), deposit: Zero::zero() });
+		TotalSupply::insert(TEST_ASSET, 1000);
+		PoolBalance::insert(TEST_ASSET, 0);
+		for i in 0 .. a {
+			let holder: T::AccountId = account("holder", i, SEED);
+			<Balances<T>>::insert(TEST_ASSET, &holder, 1);
+		}
+	}: destroy_asset (RawOrigin::Signed(caller.clone()), TEST_ASSET, a)
+	verify {
+		assert_last_event::<T>(RawEvent::Destroyed(TEST_ASSET, caller).into());
+		assert!(<Asset<T>>::get(TEST_ASSET).is_none());
+	}
 }
 
 #[cfg(test)]
@@ -137,6 +416,55 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn set_min_balance() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_set_min_balance::<Test>());
+		});
+	}
+
+	#[test]
+	fn set_pool_cap() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_set_pool_cap::<Test>());
+		});
+	}
+
+	#[test]
+	fn set_reclaim_limit() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_set_reclaim_limit::<Test>());
+		});
+	}
+
+	#[test]
+	fn pause() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_pause::<Test>());
+		});
+	}
+
+	#[test]
+	fn unpause() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_unpause::<Test>());
+		});
+	}
+
+	#[test]
+	fn destroy_asset() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_destroy_asset::<Test>());
+		});
+	}
+
+	#[test]
+	fn create_asset() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_create_asset::<Test>());
+		});
+	}
+
 	#[test]
 	fn transfer_asset() {
 		ExtBuilder::default().build().execute_with(|| {
@@ -158,10 +486,24 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn batch_private_transfer() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_batch_private_transfer::<Test>());
+		});
+	}
+
 	#[test]
 	fn reclaim() {
 		ExtBuilder::default().build().execute_with(|| {
 			assert_ok!(test_benchmark_reclaim::<Test>());
 		});
 	}
+
+	#[test]
+	fn set_auditor_key() {
+		ExtBuilder::default().build().execute_with(|| {
+			assert_ok!(test_benchmark_set_auditor_key::<Test>());
+		});
+	}
 }
@@ -25,6 +25,7 @@ use ark_std::rand::{RngCore, SeedableRng};
 use manta_api::{ReclaimCircuit, TransferCircuit};
 use manta_asset::*;
 use manta_crypto::*;
+use manta_data::*;
 use rand_chacha::ChaCha20Rng;
 
 /// this is a local test on zero knowledge proof generation and verifications
@@ -317,6 +318,119 @@ fn test_transfer_helper(
 	.concat();
 	let pvk = Groth16Pvk::from(pk.vk.clone());
 	assert!(verify_proof(&pvk, &proof, &inputs[..]).unwrap());
+
+	// the batched verifier agrees with the single-proof verifier
+	let instance = (proof, inputs);
+	assert!(batch_verify::batch_verify(
+		&pvk,
+		&[instance.clone(), instance.clone()]
+	));
+	assert!(batch_verify::batch_verify(&pvk, &[instance.clone()]));
+	let mut tampered = instance.clone();
+	tampered.1.swap(0, 1);
+	assert!(!verify_proof(&pvk, &tampered.0, &tampered.1[..]).unwrap());
+	assert!(!batch_verify::batch_verify(&pvk, &[tampered.clone()]));
+	assert!(!batch_verify::batch_verify(&pvk, &[instance, tampered]));
+}
+
+/// the batched verifier accepts the precomputed transfer,
+/// and rejects a batch where one of the transfers does not match its proof
+#[test]
+fn test_batch_verify_precomputed_transfer() {
+	let data = PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+		.unwrap();
+	let transfer_key = batch_verify::prepare_key(&TRANSFER_PK).unwrap();
+	assert!(data.verify(&TRANSFER_PK));
+	assert!(batch_verify::batch_verify_transfers(&transfer_key, &[data]));
+
+	let data = PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+		.unwrap();
+	let mut tampered =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	tampered.sender_1.void_number = data.sender_2.void_number;
+	tampered.sender_2.void_number = data.sender_1.void_number;
+	assert!(!tampered.verify(&TRANSFER_PK));
+	assert!(!batch_verify::batch_verify_transfers(
+		&transfer_key,
+		&[data, tampered]
+	));
+}
+
+/// the single-proof verifications with prepared keys agree with `verify`
+#[test]
+fn test_verify_with_prepared_keys() {
+	let transfer_key = batch_verify::prepare_key(&TRANSFER_PK).unwrap();
+	let reclaim_key = batch_verify::prepare_key(&RECLAIM_PK).unwrap();
+	// the stored keys leave out the line coefficients of gamma and delta
+	assert!(transfer_key.len() < 8 * 1024);
+	assert!(reclaim_key.len() < 8 * 1024);
+
+	let transfer =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	assert!(batch_verify::verify_transfer(&transfer_key, &transfer));
+	assert!(!batch_verify::verify_transfer(&reclaim_key, &transfer));
+
+	let mut reclaim =
+		ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+	assert!(reclaim.verify(&RECLAIM_PK));
+	assert!(batch_verify::verify_reclaim(&reclaim_key, &reclaim));
+	reclaim.reclaim_amount += 1;
+	assert!(!reclaim.verify(&RECLAIM_PK));
+	assert!(!batch_verify::verify_reclaim(&reclaim_key, &reclaim));
+
+	// a key that does not deserialize fails every verification
+	assert!(!batch_verify::verify_transfer(&[0u8; 32], &transfer));
+}
+
+/// the precomputed payloads pass the strict payload checks,
+/// and tampering with their points is caught by the matching check
+#[test]
+fn test_payload_check_precomputed_coins() {
+	for payload in [
+		precomputed_coins::TEST_MINT_10_PAYLOAD,
+		precomputed_coins::TEST_MINT_11_PAYLOAD,
+	]
+	.iter()
+	{
+		let data = MintData::deserialize(payload.as_ref()).unwrap();
+		assert_eq!(payload_check::check_mint(&data), Ok(()));
+	}
+	let transfer =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	assert_eq!(payload_check::check_transfer(&transfer), Ok(()));
+	let reclaim =
+		ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+	assert_eq!(payload_check::check_reclaim(&reclaim), Ok(()));
+
+	// the negation of A is a well formed point: only the verification rejects it
+	let mut tampered =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	tampered.proof[47] ^= 0x80;
+	assert_eq!(payload_check::check_transfer(&tampered), Ok(()));
+	assert!(!tampered.verify(&TRANSFER_PK));
+
+	// the zero proof starts with (0, 2), which is on G1 but not in the subgroup
+	let mut tampered =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	tampered.proof = [0u8; 192];
+	assert_eq!(
+		payload_check::check_transfer(&tampered),
+		Err(payload_check::PayloadError::PointNotInSubgroup)
+	);
+
+	// the root of the second sender is checked as well
+	let mut tampered =
+		ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+	tampered.sender_2.root = [0xffu8; 32];
+	assert_eq!(
+		payload_check::check_reclaim(&tampered),
+		Err(payload_check::PayloadError::NonCanonicalFieldElement)
+	);
 }
 
 /// this is a local test on zero knowledge proof generation and verifications
@@ -564,3 +678,89 @@ fn test_reclaim_helper(
 	let pvk = Groth16Pvk::from(pk.vk.clone());
 	assert!(verify_proof(&pvk, &proof, &inputs[..]).unwrap());
 }
+
+/// the host functions give the same results as the computations inside the runtime,
+/// both when called directly and through the `native-crypto` feature
+#[test]
+fn test_host_functions_match_runtime() {
+	use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+	use ark_ec::ProjectiveCurve;
+	use ark_ff::PrimeField;
+	use ark_serialize::CanonicalSerialize;
+	use ark_std::UniformRand;
+
+	let encode = |pairs: &[(G1Affine, G2Affine)]| -> Vec<(Vec<u8>, Vec<u8>)> {
+		pairs
+			.iter()
+			.map(|(g1, g2)| {
+				let mut g1_bytes = Vec::new();
+				let mut g2_bytes = Vec::new();
+				g1.serialize(&mut g1_bytes).unwrap();
+				g2.serialize(&mut g2_bytes).unwrap();
+				(g1_bytes, g2_bytes)
+			})
+			.collect()
+	};
+
+	let mut rng = ChaCha20Rng::from_seed([9u8; 32]);
+
+	// e(aP, Q) * e(-P, aQ) is one, e(P, Q) is not
+	let p = G1Projective::rand(&mut rng);
+	let q = G2Projective::rand(&mut rng);
+	let a = Fr::rand(&mut rng);
+	let valid = vec![
+		(p.mul(a.into_repr()).into_affine(), q.into_affine()),
+		((-p).into_affine(), q.mul(a.into_repr()).into_affine()),
+	];
+	let invalid = vec![(p.into_affine(), q.into_affine())];
+	assert!(primitives::runtime_pairing_check(&valid));
+	assert!(primitives::pairing_check(&valid));
+	assert!(manta_pay_host::native::pairing_check(&encode(&valid)));
+	assert!(!primitives::runtime_pairing_check(&invalid));
+	assert!(!primitives::pairing_check(&invalid));
+	assert!(!manta_pay_host::native::pairing_check(&encode(&invalid)));
+
+	// the coin ledger updates, with several coins in the same shard
+	let hash_param = HashParam::deserialize(HASH_PARAM.data).unwrap();
+	let hash_param_checksum = HASH_PARAM.get_checksum().unwrap();
+	let mut runtime_ledger = MantaPrivateAssetLedger::default();
+	let mut ledger = MantaPrivateAssetLedger::default();
+	let mut native_shards = vec![MantaPrivateAssetLedger::default().shard[0].encode(); 2];
+	for i in 0..8u8 {
+		let mut cm = [0u8; 32];
+		rng.fill_bytes(&mut cm);
+		cm[0] = i % 2;
+		assert!(primitives::runtime_ledger_update(
+			&mut runtime_ledger,
+			&cm,
+			&hash_param
+		));
+		assert!(primitives::ledger_update(&mut ledger, &cm, &hash_param));
+		let native_shard = &mut native_shards[cm[0] as usize];
+		// the host refuses to hash with parameters other than those of the runtime
+		assert_eq!(
+			manta_pay_host::native::ledger_update(native_shard, &cm, &[0u8; 32]),
+			None
+		);
+		*native_shard =
+			manta_pay_host::native::ledger_update(native_shard, &cm, &hash_param_checksum).unwrap();
+		assert_eq!(ledger.encode(), runtime_ledger.encode());
+		assert_eq!(*native_shard, runtime_ledger.shard[cm[0] as usize].encode());
+	}
+
+	// the proof verifications of the pallet
+	let transfer =
+		PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+			.unwrap();
+	assert_eq!(
+		batch_verify::verify_transfer(&batch_verify::prepare_key(&TRANSFER_PK).unwrap(), &transfer),
+		transfer.verify(&TRANSFER_PK)
+	);
+	let reclaim =
+		ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+	assert_eq!(
+		batch_verify::verify_reclaim(&batch_verify::prepare_key(&RECLAIM_PK).unwrap(), &reclaim),
+		reclaim.verify(&RECLAIM_PK)
+	);
+	assert!(reclaim.verify(&RECLAIM_PK));
+}
@@ -15,10 +15,19 @@
 // along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate as pallet_manta_pay;
-use crate::*;
-use ark_serialize::CanonicalDeserialize;
+use crate::{
+	wallet::{decode_note, Auditor, LedgerNote, ViewTagRng, Wallet},
+	*,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::{RngCore, SeedableRng};
-use frame_support::{assert_noop, assert_ok, parameter_types};
+use frame_support::{
+	assert_noop, assert_ok, parameter_types,
+	storage::{migration, IterableStorageDoubleMap, IterableStorageMap},
+	traits::{Contains, OnRuntimeUpgrade},
+	weights::GetDispatchInfo,
+	Blake2_128Concat, StorageHasher,
+};
 use manta_api::{
 	generate_mint_payload, generate_private_transfer_payload, generate_reclaim_payload,
 	write_zkp_keys,
@@ -31,7 +40,7 @@ use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
 };
-use std::{boxed::Box, fs::File, io::prelude::*, string::String, sync::Once};
+use std::{boxed::Box, cell::RefCell, fs::File, io::prelude::*, string::String, sync::Once};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -44,6 +53,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		PalletBalances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		MantaModule: pallet_manta_pay::{Module, Call, Storage, Event<T>},
 	}
 );
@@ -65,11 +75,11 @@ impl frame_system::Config for Test {
 	type AccountId = u64;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = Event;
 	type BlockHashCount = BlockHashCount;
 	type DbWeight = ();
 	type Version = ();
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -79,20 +89,194 @@ impl frame_system::Config for Test {
 	type SS58Prefix = SS58Prefix;
 }
 
+/// Only account 1 may create assets.
+pub struct AssetCreators;
+impl Contains<u64> for AssetCreators {
+	fn sorted_members() -> Vec<u64> {
+		vec![1]
+	}
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const AssetDeposit: u64 = 10;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
 impl Config for Test {
-	type Event = ();
+	type Event = Event;
+	type Currency = PalletBalances;
+	type AssetDeposit = AssetDeposit;
+	type PauseOrigin = frame_system::EnsureRoot<u64>;
+	type CreateOrigin = frame_system::EnsureSignedBy<AssetCreators, u64>;
+	type Verifier = TestVerifier;
+	type LedgerHash = TestLedgerHash;
 	type WeightInfo = ();
 }
 type Assets = Module<Test>;
 
+/// A failure of the parameters, the keys or the ledger hash, injected with `inject_fault`.
+#[derive(Clone, Copy, PartialEq)]
+enum Fault {
+	HashParamDes,
+	HashParamChecksum,
+	CommitParamDes,
+	CommitParamChecksum,
+	ZkpKeyChecksum,
+	ZkpKeyPrepare,
+	LedgerUpdate,
+}
+
+thread_local! {
+	static FAULT: RefCell<Option<Fault>> = RefCell::new(None);
+}
+
+/// Make `TestVerifier` or `TestLedgerHash` fail on `fault`, until the next `new_test_ext`.
+fn inject_fault(fault: Fault) {
+	FAULT.with(|f| *f.borrow_mut() = Some(fault));
+}
+
+fn has_fault(fault: Fault) -> bool {
+	FAULT.with(|f| *f.borrow() == Some(fault))
+}
+
+/// `Groth16`, failing on the injected `Fault`.
+pub struct TestVerifier;
+
+impl Verifier for TestVerifier {
+	fn commit_param_checksum() -> Option<[u8; 32]> {
+		if has_fault(Fault::CommitParamChecksum) {
+			return None;
+		}
+		Groth16::commit_param_checksum()
+	}
+
+	fn commit_param() -> Option<CommitmentParam> {
+		if has_fault(Fault::CommitParamDes) {
+			return None;
+		}
+		Groth16::commit_param()
+	}
+
+	fn transfer_key_checksum() -> Option<[u8; 32]> {
+		if has_fault(Fault::ZkpKeyChecksum) {
+			return None;
+		}
+		Groth16::transfer_key_checksum()
+	}
+
+	fn reclaim_key_checksum() -> Option<[u8; 32]> {
+		if has_fault(Fault::ZkpKeyChecksum) {
+			return None;
+		}
+		Groth16::reclaim_key_checksum()
+	}
+
+	fn prepare_transfer_key() -> Option<Vec<u8>> {
+		if has_fault(Fault::ZkpKeyPrepare) {
+			return None;
+		}
+		Groth16::prepare_transfer_key()
+	}
+
+	fn prepare_reclaim_key() -> Option<Vec<u8>> {
+		if has_fault(Fault::ZkpKeyPrepare) {
+			return None;
+		}
+		Groth16::prepare_reclaim_key()
+	}
+
+	fn verify_transfer(prepared_key: &[u8], data: &PrivateTransferData) -> bool {
+		Groth16::verify_transfer(prepared_key, data)
+	}
+
+	fn batch_verify_transfers(prepared_key: &[u8], data: &[PrivateTransferData]) -> bool {
+		Groth16::batch_verify_transfers(prepared_key, data)
+	}
+
+	fn verify_reclaim(prepared_key: &[u8], data: &ReclaimData) -> bool {
+		Groth16::verify_reclaim(prepared_key, data)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_transfer_payload() -> PrivateTransferPayload {
+		Groth16::benchmark_transfer_payload()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_batch() -> Vec<([MintPayload; 2], PrivateTransferPayload)> {
+		Groth16::benchmark_batch()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_reclaim_payload() -> ReclaimPayload {
+		Groth16::benchmark_reclaim_payload()
+	}
+}
+
+/// `Pedersen`, failing on the injected `Fault`.
+pub struct TestLedgerHash;
+
+impl LedgerHash for TestLedgerHash {
+	type Param = HashParam;
+
+	fn param_checksum() -> Option<[u8; 32]> {
+		if has_fault(Fault::HashParamChecksum) {
+			return None;
+		}
+		Pedersen::param_checksum()
+	}
+
+	fn param() -> Option<HashParam> {
+		if has_fault(Fault::HashParamDes) {
+			return None;
+		}
+		Pedersen::param()
+	}
+
+	fn update(ledger: &mut MantaPrivateAssetLedger, cm: &[u8; 32], param: &HashParam) -> bool {
+		if has_fault(Fault::LedgerUpdate) {
+			return false;
+		}
+		Pedersen::update(ledger, cm, param)
+	}
+
+	fn witness_path(param: &HashParam, leaves: &[[u8; 32]], index: usize) -> Option<Vec<u8>> {
+		Pedersen::witness_path(param, leaves, index)
+	}
+}
+
 fn new_test_ext() -> sp_io::TestExternalities {
-	frame_system::GenesisConfig::default()
+	let mut t = frame_system::GenesisConfig::default()
 		.build_storage::<Test>()
-		.unwrap()
-		.into()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 5)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext: sp_io::TestExternalities = t.into();
+	FAULT.with(|f| *f.borrow_mut() = None);
+	// events are not deposited in the genesis block
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
 
-// todo: write must-fail tests for cross-asset-id tests
+fn last_event() -> Event {
+	System::events().pop().expect("an event is expected").event
+}
+
+// todo: write must-fail tests for cross-asset-id tests,
+// once the transfer circuit binds the asset id
 // Misc tests:
 
 #[test]
@@ -107,6 +291,26 @@ fn test_constants_should_work() {
 		let commit_param_checksum = CommitParamChecksum::get();
 		assert_eq!(hash_param_checksum, hash_param_checksum_local);
 		assert_eq!(commit_param_checksum, commit_param_checksum_local);
+
+		// the ledger stores the parameter checksum of the configured ledger hash
+		assert_eq!(
+			Some(hash_param_checksum),
+			<Test as Config>::LedgerHash::param_checksum()
+		);
+
+		// the ledger stores the key checksums of the configured verifier
+		assert_eq!(
+			Some(Assets::transfer_zkp_vk_checksum()),
+			<Test as Config>::Verifier::transfer_key_checksum()
+		);
+		assert_eq!(
+			Some(Assets::reclaim_zkp_vk_checksum()),
+			<Test as Config>::Verifier::reclaim_key_checksum()
+		);
+		assert_eq!(
+			Groth16::transfer_key_checksum(),
+			TRANSFER_PK.get_checksum().ok()
+		);
 	});
 }
 
@@ -131,307 +335,1919 @@ fn querying_total_supply_should_work() {
 		assert_eq!(Assets::total_supply(TEST_ASSET), 100);
 	});
 }
-#[ignore]
 #[test]
-fn destroying_asset_balance_with_positive_balance_should_work() {
-	unimplemented!();
+fn querying_ledger_state_should_work() {
+	new_test_ext().execute_with(|| {
+		assert!(!Assets::is_initialized(TEST_ASSET));
+		initialize_test(10_000_000);
+		assert!(Assets::is_initialized(TEST_ASSET));
+		assert_eq!(Assets::pool_balance(TEST_ASSET), 0);
+		assert_eq!(Assets::public_supply(TEST_ASSET), 10_000_000);
+		assert_eq!(Assets::coin_count(), 0);
+		assert_eq!(Assets::note_count(), 0);
+		assert_eq!(Assets::void_number_count(), 0);
+
+		// 10 + 11 + 12 + 13
+		mint_tokens_helper(4);
+		assert_eq!(Assets::pool_balance(TEST_ASSET), 46);
+		assert_eq!(Assets::public_supply(TEST_ASSET), 10_000_000 - 46);
+		assert_eq!(Assets::total_supply(TEST_ASSET), 10_000_000);
+		assert_eq!(Assets::coin_count(), 4);
+		assert_eq!(Assets::note_count(), 4);
+		assert_eq!(Assets::void_number_count(), 0);
+	});
 }
 
-// Init tests:
+#[test]
+fn querying_coin_witness_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let hash_param = HashParam::deserialize(HASH_PARAM.data).unwrap();
+		let senders = mint_tokens_helper(4);
+		let coin_shards = CoinShards::get();
+
+		for i in 0usize..senders.len() {
+			let (sender, _) = build_sender_meta_data(&senders, &hash_param, i, i);
+			let witness = Assets::coin_witness(&senders[i].utxo).unwrap();
+
+			assert_eq!(witness.shard_index, senders[i].utxo[0]);
+			assert_eq!(
+				coin_shards.shard[witness.shard_index as usize].list[witness.leaf_index as usize],
+				senders[i].utxo
+			);
+			assert_eq!(witness.root, sender.root);
+			assert!(coin_shards.check_root(&witness.root));
+
+			let mut path_bytes = Vec::new();
+			sender.membership.serialize(&mut path_bytes).unwrap();
+			assert_eq!(witness.path, path_bytes);
+		}
+	});
+}
 
 #[test]
-fn cannot_init_twice() {
+fn querying_coin_witness_of_unknown_coin_should_return_none() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Assets::init_asset(Origin::signed(1), TEST_ASSET, 100));
-		assert_noop!(
-			Assets::init_asset(Origin::signed(1), TEST_ASSET, 100),
-			Error::<Test>::AlreadyInitialized
-		);
+		initialize_test(10_000_000);
+		mint_tokens_helper(2);
+		assert_eq!(Assets::coin_witness(&[7u8; 32]), None);
 	});
 }
 
-// Mint tests:
+#[test]
+fn querying_coin_witness_with_mismatched_hash_param_should_return_none() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+		let senders = mint_tokens_helper(1);
+		HashParamChecksum::put([3u8; 32]);
+		assert_eq!(Assets::coin_witness(&senders[0].utxo), None);
+	});
+}
 
 #[test]
-fn test_mint_should_work() {
+fn destroying_asset_balance_with_positive_balance_should_work() {
 	new_test_ext().execute_with(|| {
-		initialize_test(1000);
+		assert_ok!(Assets::create_asset(Origin::signed(1), 100));
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, 0, 10));
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 3, 0, 10));
+		assert_ok!(Assets::set_auditor_key(Origin::root(), 0, Some([9u8; 32])));
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::TransferAsset,
+			Some(0)
+		));
 
-		let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data).unwrap();
-		let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
-		let mut sk = [0u8; 32];
-		rng.fill_bytes(&mut sk);
-		let asset = MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &10).unwrap();
+		assert_ok!(Assets::destroy_asset(Origin::signed(1), 0, 2));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::PartiallyDestroyed(0, 2))
+		);
+		assert!(!Assets::is_initialized(0));
+		assert_eq!(AuditorKeys::get(0), None);
+		assert!(Assets::paused(PausableOperation::TransferAsset, Some(0)).is_none());
+		assert!(Assets::asset(0).is_some());
 
-		let payload = generate_mint_payload(&asset);
-		assert_ok!(Assets::mint_private_asset(
-			Origin::signed(1),
-			payload.unwrap()
-		));
+		// the asset cannot be used nor re-issued while it is being destroyed
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, 0, 10),
+			Error::<Test>::BasecoinNotInit
+		);
+		assert_noop!(
+			Assets::init_asset(Origin::signed(1), 0, 100),
+			Error::<Test>::AlreadyInitialized
+		);
 
-		assert_eq!(TotalSupply::get(TEST_ASSET), 1000);
-		assert_eq!(PoolBalance::get(TEST_ASSET), 10);
-		let coin_shards = CoinShards::get();
-		assert!(coin_shards.exist(&asset.utxo));
-		let vn_list = VNList::get();
-		assert_eq!(vn_list.len(), 0);
+		assert_ok!(Assets::destroy_asset(Origin::signed(1), 0, 2));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::Destroyed(0, 1))
+		);
+		assert!(Assets::asset(0).is_none());
+		assert_eq!(<Balances<Test>>::iter_prefix(0).count(), 0);
+		assert_eq!(PalletBalances::reserved_balance(1), 0);
+		assert_eq!(PalletBalances::free_balance(1), 100);
+
+		// the id can be used again, and is not paused
+		assert_ok!(Assets::init_asset(Origin::signed(1), 0, 100));
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, 0, 10));
 	});
 }
 
 #[test]
-fn mint_without_init_should_not_work() {
+fn destroy_asset_requires_owner() {
 	new_test_ext().execute_with(|| {
-		let payload = generate_mint_payload_helper(100);
-
+		assert_ok!(Assets::create_asset(Origin::signed(1), 100));
+		assert_noop!(
+			Assets::destroy_asset(Origin::signed(2), 0, 10),
+			Error::<Test>::NoPermission
+		);
 		assert_noop!(
-			Assets::mint_private_asset(Origin::signed(1), payload),
+			Assets::destroy_asset(Origin::signed(1), 1, 10),
 			Error::<Test>::BasecoinNotInit
 		);
 	});
 }
 
 #[test]
-fn mint_zero_amount_should_work() {
+fn destroy_asset_with_private_coins_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(100);
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+		assert_noop!(
+			Assets::destroy_asset(Origin::signed(1), TEST_ASSET, 10),
+			Error::<Test>::PoolNotEmpty
+		);
+	});
+}
 
-		let payload = generate_mint_payload_helper(0);
+#[test]
+fn private_call_weights_should_grow_with_the_ledger() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
 
-		assert_ok!(Assets::mint_private_asset(Origin::signed(1), payload));
+		let payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		let transfer_weight = || {
+			pallet_manta_pay::Call::<Test>::private_transfer(
+				payload,
+				[None, None],
+				[None, None],
+				[None, None],
+			)
+			.get_dispatch_info()
+			.weight
+		};
+		let empty_ledger_weight = transfer_weight();
+
+		// two more notes and coins
+		mint_tokens_helper(2);
+		let minted_weight = transfer_weight();
+		assert!(minted_weight > empty_ledger_weight);
+
+		// two more void numbers
+		VNList::mutate(|list| list.extend_from_slice(&[[1u8; 32], [2u8; 32]]));
+		assert!(transfer_weight() > minted_weight);
 	});
 }
 
 #[test]
-fn mint_with_insufficient_origin_balance_should_not_work() {
+fn private_call_weights_should_grow_with_the_target_shard() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
-
-		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 99));
-		assert_eq!(Assets::balance(1, TEST_ASSET), 1);
-		assert_eq!(Assets::balance(2, TEST_ASSET), 99);
+		initialize_test(10_000_000);
 
-		let payload = generate_mint_payload_helper(50);
+		let payload = generate_mint_payload_helper(10);
+		let cm = MintData::deserialize(payload.as_ref()).unwrap().cm;
+		let mint_weight = || {
+			pallet_manta_pay::Call::<Test>::mint_private_asset(payload, None, None)
+				.get_dispatch_info()
+				.weight
+		};
+		let empty_shard_weight = mint_weight();
 
-		assert_noop!(
-			Assets::mint_private_asset(Origin::signed(1), payload),
-			Error::<Test>::BalanceLow
-		);
+		// coins in the other shards do not change the weight
+		let mut coin_shards = CoinShards::get();
+		let other_shard = cm[0].wrapping_add(1) as usize;
+		coin_shards.shard[other_shard]
+			.list
+			.extend_from_slice(&[[1u8; 32], [2u8; 32]]);
+		CoinShards::put(&coin_shards);
+		assert_eq!(mint_weight(), empty_shard_weight);
+
+		// coins in the shard of the new coin do
+		coin_shards.shard[cm[0] as usize]
+			.list
+			.extend_from_slice(&[[3u8; 32], [4u8; 32]]);
+		CoinShards::put(&coin_shards);
+		assert!(mint_weight() > empty_shard_weight);
+
+		// two new coins in the same shard are charged for the shard with the first one
+		assert_eq!(Assets::shard_size(&[cm]), 2);
+		assert_eq!(Assets::shard_size(&[cm, cm]), 3);
+		assert_eq!(Assets::shard_size(&[]), 0);
 	});
 }
 
+// Init tests:
+
 #[test]
-fn mint_with_existing_coin_should_not_work() {
+fn cannot_init_twice() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
-
-		let payload = generate_mint_payload_helper(50);
-		assert_ok!(Assets::mint_private_asset(Origin::signed(1), payload));
-
+		assert_ok!(Assets::init_asset(Origin::signed(1), TEST_ASSET, 100));
 		assert_noop!(
-			Assets::mint_private_asset(Origin::signed(1), payload),
-			Error::<Test>::MantaCoinExist
+			Assets::init_asset(Origin::signed(1), TEST_ASSET, 100),
+			Error::<Test>::AlreadyInitialized
 		);
 	});
 }
 
 #[test]
-fn mint_with_invalid_commitment_should_not_work() {
+fn init_asset_requires_create_origin() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
-
-		let commit_param = CommitmentParam::deserialize(
-			Parameter {
-				data: &[0u8; 81664],
-			}
-			.data,
-		)
-		.unwrap();
-		let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
-		let mut sk = [0u8; 32];
-		rng.fill_bytes(&mut sk);
-		let asset = MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &50).unwrap();
-		let payload = generate_mint_payload(&asset).unwrap();
-
 		assert_noop!(
-			Assets::mint_private_asset(Origin::signed(1), payload),
-			Error::<Test>::MintFail
+			Assets::init_asset(Origin::signed(2), TEST_ASSET, 100),
+			sp_runtime::DispatchError::BadOrigin
 		);
+		assert!(!Assets::is_initialized(TEST_ASSET));
+		assert!(!Assets::ledger_initialized());
 	});
 }
 
 #[test]
-fn mint_with_hash_param_mismatch_should_not_work() {
+fn init_with_a_faulty_parameter_or_key_should_not_work() {
+	for (fault, error) in vec![
+		(Fault::HashParamDes, Error::<Test>::HashParamDesFail),
+		(
+			Fault::HashParamChecksum,
+			Error::<Test>::HashParamChecksumFail,
+		),
+		(Fault::CommitParamDes, Error::<Test>::CommitParamDesFail),
+		(
+			Fault::CommitParamChecksum,
+			Error::<Test>::CommitParamChecksumFail,
+		),
+		(Fault::ZkpKeyChecksum, Error::<Test>::ZkpKeyChecksumFail),
+		(Fault::ZkpKeyPrepare, Error::<Test>::ZkpKeyPrepareFail),
+	] {
+		new_test_ext().execute_with(|| {
+			inject_fault(fault);
+			assert_noop!(
+				Assets::init_asset(Origin::signed(1), TEST_ASSET, 100),
+				error
+			);
+		});
+	}
+}
+
+#[test]
+fn init_second_asset_should_not_reset_ledger() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
+		initialize_test(10_000_000);
+		mint_tokens_helper(2);
+		assert!(Assets::ledger_initialized());
 
-		let payload = generate_mint_payload_helper(50);
-		assert_ok!(Assets::mint_private_asset(Origin::signed(1), payload));
+		let coin_count = Assets::coin_count();
+		let note_count = Assets::note_count();
+		let hash_param_checksum = HashParamChecksum::get();
+		let pool_balance = Assets::pool_balance(TEST_ASSET);
 
-		HashParamChecksum::put([3u8; 32]);
+		assert_ok!(Assets::init_asset(Origin::signed(1), TEST_ASSET + 1, 100));
 
-		assert_noop!(
-			Assets::mint_private_asset(Origin::signed(1), payload),
-			Error::<Test>::ParamFail
-		);
+		assert_eq!(Assets::coin_count(), coin_count);
+		assert_eq!(Assets::note_count(), note_count);
+		assert_eq!(HashParamChecksum::get(), hash_param_checksum);
+		assert_eq!(Assets::pool_balance(TEST_ASSET), pool_balance);
+		assert_eq!(Assets::pool_balance(TEST_ASSET + 1), 0);
+		assert_eq!(Assets::balance(1, TEST_ASSET + 1), 100);
+
+		// a ledger initialized by this release needs no migration
+		assert_eq!(Assets::storage_version(), Releases::V2);
+		assert_eq!(Assets::on_runtime_upgrade(), 0);
 	});
 }
 
 #[test]
-fn mint_with_commit_param_mismatch_should_not_work() {
+fn upgrading_an_initialized_ledger_should_not_reset_it() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
+		initialize_test(10_000_000);
+		mint_tokens_helper(2);
+		VNList::put(vec![[1u8; 32]]);
 
-		let payload = generate_mint_payload_helper(50);
-		assert_ok!(Assets::mint_private_asset(Origin::signed(1), payload));
+		// the storage of the first release has no flag
+		LedgerInitialized::kill();
+		StorageVersion::put(Releases::V1);
 
-		CommitParamChecksum::put([3u8; 32]);
+		let coin_count = Assets::coin_count();
+		let note_count = Assets::note_count();
+		assert!(note_count > 0);
 
-		assert_noop!(
-			Assets::mint_private_asset(Origin::signed(1), payload),
-			Error::<Test>::ParamFail
-		);
+		Assets::on_runtime_upgrade();
+		assert!(Assets::ledger_initialized());
+		assert_eq!(Assets::storage_version(), Releases::V2);
+
+		assert_ok!(Assets::init_asset(Origin::signed(1), TEST_ASSET + 1, 100));
+		assert_eq!(Assets::coin_count(), coin_count);
+		assert_eq!(Assets::note_count(), note_count);
+		assert_eq!(VNList::get(), vec![[1u8; 32]]);
 	});
 }
 
-// Transfer tests:
-
 #[test]
-fn test_transfer_should_work() {
-	new_test_ext().execute_with(|| transfer_test_helper(1));
+fn upgrading_should_key_balances_by_asset() {
+	new_test_ext().execute_with(|| {
+		// the first release keyed the balances by account first
+		let put_balance = |who: u64, asset_id: AssetId, balance: AssetBalance| {
+			let key = [
+				Blake2_128Concat::hash(&who.encode()),
+				Blake2_128Concat::hash(&asset_id.encode()),
+			]
+			.concat();
+			migration::put_storage_value(b"Assets", b"Balances", &key, balance);
+		};
+		put_balance(1, TEST_ASSET, 100);
+		put_balance(1, TEST_ASSET + 1, 50);
+		put_balance(2, TEST_ASSET, 20);
+
+		Assets::on_runtime_upgrade();
+		assert_eq!(Assets::balance(1, TEST_ASSET), 100);
+		assert_eq!(Assets::balance(1, TEST_ASSET + 1), 50);
+		assert_eq!(Assets::balance(2, TEST_ASSET), 20);
+		assert_eq!(<Balances<Test>>::iter().count(), 3);
+		assert_eq!(<Balances<Test>>::iter_prefix(TEST_ASSET).count(), 2);
+	});
 }
 
-#[ignore]
 #[test]
-fn test_transfer_should_work_super_long() {
-	new_test_ext().execute_with(|| transfer_test_helper(400));
+fn upgrading_a_chain_without_assets_should_not_initialize_the_ledger() {
+	new_test_ext().execute_with(|| {
+		Assets::on_runtime_upgrade();
+		assert!(!Assets::ledger_initialized());
+		assert_eq!(Assets::storage_version(), Releases::V2);
+	});
 }
 
 #[test]
-fn transferring_amount_below_available_balance_should_work() {
+fn create_asset_should_assign_increasing_ids() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
-		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 50));
-		assert_eq!(Assets::balance(1, TEST_ASSET), 50);
-		assert_eq!(Assets::balance(2, TEST_ASSET), 50);
+		assert_ok!(Assets::create_asset(Origin::signed(1), 100));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::Issued(0, 1, 100))
+		);
+		assert_ok!(Assets::create_asset(Origin::signed(1), 200));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::Issued(1, 1, 200))
+		);
+		assert_eq!(Assets::total_supply(0), 100);
+		assert_eq!(Assets::total_supply(1), 200);
+		assert_eq!(Assets::balance(1, 1), 200);
+		assert_eq!(Assets::next_asset_id(), 2);
 	});
 }
 
 #[test]
-fn transferring_amount_more_than_available_balance_should_not_work() {
+fn create_asset_should_skip_ids_taken_by_init_asset() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
-		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 50));
-		assert_eq!(Assets::balance(1, TEST_ASSET), 50);
-		assert_eq!(Assets::balance(2, TEST_ASSET), 50);
-		assert_noop!(
-			Assets::transfer_asset(Origin::signed(1), 1, TEST_ASSET, 60),
-			Error::<Test>::BalanceLow
-		);
+		assert_ok!(Assets::init_asset(Origin::signed(1), 0, 100));
+		assert_ok!(Assets::init_asset(Origin::signed(1), 1, 100));
+		assert_ok!(Assets::create_asset(Origin::signed(1), 300));
+		assert_eq!(Assets::total_supply(2), 300);
+		assert_eq!(Assets::next_asset_id(), 3);
 	});
 }
 
 #[test]
-fn transferring_less_than_one_unit_should_not_work() {
+fn create_asset_should_skip_an_asset_being_destroyed() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
+		assert_ok!(Assets::init_asset(Origin::signed(1), 0, 100));
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, 0, 50));
+		assert_ok!(Assets::destroy_asset(Origin::signed(1), 0, 1));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::PartiallyDestroyed(0, 1))
+		);
+
+		assert_ok!(Assets::create_asset(Origin::signed(1), 300));
+		assert_eq!(Assets::total_supply(1), 300);
+		assert_eq!(Assets::next_asset_id(), 2);
+	});
+}
+
+#[test]
+fn create_asset_should_search_a_bounded_number_of_ids() {
+	new_test_ext().execute_with(|| {
+		for asset_id in 0..MAX_ASSET_ID_SEARCH as AssetId {
+			assert_ok!(Assets::init_asset(Origin::signed(1), asset_id, 100));
+		}
+		assert_noop!(
+			Assets::create_asset(Origin::signed(1), 300),
+			Error::<Test>::NoAvailableAssetId
+		);
+
+		// a free id within the search is found
+		assert_ok!(Assets::destroy_asset(
+			Origin::signed(1),
+			MAX_ASSET_ID_SEARCH as AssetId - 1,
+			1
+		));
+		assert_ok!(Assets::create_asset(Origin::signed(1), 300));
+		assert_eq!(
+			Assets::total_supply(MAX_ASSET_ID_SEARCH as AssetId - 1),
+			300
+		);
+	});
+}
+
+#[test]
+fn create_asset_should_reserve_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::create_asset(Origin::signed(1), 100));
+		assert_eq!(PalletBalances::reserved_balance(1), 10);
+		assert_eq!(PalletBalances::free_balance(1), 90);
+		assert_eq!(
+			Assets::asset(0),
+			Some(AssetDetails {
+				owner: 1,
+				deposit: 10
+			})
+		);
+
+		// assets issued through `init_asset` carry no deposit
+		assert_ok!(Assets::init_asset(Origin::signed(1), TEST_ASSET, 100));
+		assert_eq!(
+			Assets::asset(TEST_ASSET).map(|details| details.deposit),
+			Some(0)
+		);
+		assert_eq!(PalletBalances::reserved_balance(1), 10);
+	});
+}
+
+#[test]
+fn create_asset_requires_create_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Assets::create_asset(Origin::signed(2), 100),
+			sp_runtime::DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn create_asset_without_deposit_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(PalletBalances::transfer(Origin::signed(1), 3, 95));
+		assert_noop!(
+			Assets::create_asset(Origin::signed(1), 100),
+			pallet_balances::Error::<Test, pallet_balances::DefaultInstance>::InsufficientBalance
+		);
+		assert_eq!(Assets::next_asset_id(), 0);
+	});
+}
+
+// Pause tests:
+
+#[test]
+fn pause_requires_pause_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Assets::pause(Origin::signed(1), PausableOperation::Reclaim, None),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Assets::unpause(Origin::signed(1), PausableOperation::Reclaim, None),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Assets::pause(
+				Origin::root(),
+				PausableOperation::PrivateTransfer,
+				Some(TEST_ASSET)
+			),
+			Error::<Test>::UnsupportedPauseScope
+		);
+	});
+}
+
+#[test]
+fn paused_transfer_asset_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::create_asset(Origin::signed(1), 100));
+
+		// pause a single asset
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::TransferAsset,
+			Some(TEST_ASSET)
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::Paused(
+				PausableOperation::TransferAsset,
+				Some(TEST_ASSET)
+			))
+		);
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 10),
+			Error::<Test>::OperationPaused
+		);
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, 0, 10));
+
+		// pause all the assets
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::TransferAsset,
+			None
+		));
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, 0, 10),
+			Error::<Test>::OperationPaused
+		);
+
+		// the per-asset pause outlives the global one
+		assert_ok!(Assets::unpause(
+			Origin::root(),
+			PausableOperation::TransferAsset,
+			None
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::Unpaused(PausableOperation::TransferAsset, None))
+		);
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, 0, 10));
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 10),
+			Error::<Test>::OperationPaused
+		);
+		assert_ok!(Assets::unpause(
+			Origin::root(),
+			PausableOperation::TransferAsset,
+			Some(TEST_ASSET)
+		));
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 10));
+	});
+}
+
+#[test]
+fn paused_private_operations_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 4;
+		let senders = mint_tokens_helper(size);
+
+		// mint
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::MintPrivateAsset,
+			Some(TEST_ASSET)
+		));
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::OperationPaused
+		);
+
+		// private transfer
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, 2);
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::PrivateTransfer,
+			None
+		));
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::OperationPaused
+		);
+
+		// reclaim
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&mut sk,
+			&mut rng,
+			2,
+			3,
+		);
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::Reclaim,
+			None
+		));
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::OperationPaused
+		);
+		assert_ok!(Assets::unpause(
+			Origin::root(),
+			PausableOperation::Reclaim,
+			None
+		));
+		assert_ok!(Assets::reclaim(
+			Origin::signed(1),
+			payload,
+			None,
+			None,
+			None
+		));
+	});
+}
+
+// Mint tests:
+
+#[test]
+fn test_mint_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+
+		let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data).unwrap();
+		let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+		let mut sk = [0u8; 32];
+		rng.fill_bytes(&mut sk);
+		let asset = MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &10).unwrap();
+
+		let payload = generate_mint_payload(&asset);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload.unwrap(),
+			None,
+			None
+		));
+
+		assert_eq!(TotalSupply::get(TEST_ASSET), 1000);
+		assert_eq!(PoolBalance::get(TEST_ASSET), 10);
+		let coin_shards = CoinShards::get();
+		assert!(coin_shards.exist(&asset.utxo));
+		let vn_list = VNList::get();
+		assert_eq!(vn_list.len(), 0);
+		assert_eq!(
+			last_event(),
+			Event::from(RawEvent::Minted(
+				TEST_ASSET,
+				1,
+				10,
+				asset.utxo,
+				asset.utxo[0],
+				0
+			))
+		);
+	});
+}
+
+#[test]
+fn mint_should_store_view_tag() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			Some(7),
+			None
+		));
+		assert_eq!(Assets::view_tag(0), Some(7));
+	});
+}
+
+#[test]
+fn mint_without_init_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let payload = generate_mint_payload_helper(100);
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::BasecoinNotInit
+		);
+	});
+}
+
+#[test]
+fn mint_zero_amount_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let payload = generate_mint_payload_helper(0);
+
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+	});
+}
+
+#[test]
+fn mint_with_insufficient_origin_balance_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 99));
+		assert_eq!(Assets::balance(1, TEST_ASSET), 1);
+		assert_eq!(Assets::balance(2, TEST_ASSET), 99);
+
+		let payload = generate_mint_payload_helper(50);
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::BalanceLow
+		);
+	});
+}
+
+#[test]
+fn mint_failing_after_the_ledger_writes_should_not_change_storage() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		MinBalances::insert(TEST_ASSET, (10, DustPolicy::Reject));
+		let coin_count = Assets::coin_count();
+		let note_count = Assets::note_count();
+
+		// the coin, the note and the pool balance are written before the balance of
+		// the origin, which is then rejected as dust; `assert_noop` checks that the
+		// storage root is unchanged
+		let payload = generate_mint_payload_helper(95);
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::BalanceBelowMinimum
+		);
+		assert_eq!(Assets::coin_count(), coin_count);
+		assert_eq!(Assets::note_count(), note_count);
+		assert_eq!(Assets::pool_balance(TEST_ASSET), 0);
+	});
+}
+
+#[test]
+fn mint_with_full_pool_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		PoolBalance::insert(TEST_ASSET, AssetBalance::MAX);
+
+		let payload = generate_mint_payload_helper(50);
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::Overflow
+		);
+	});
+}
+
+#[test]
+fn mint_over_pool_cap_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_noop!(
+			Assets::set_pool_cap(Origin::signed(1), TEST_ASSET, Some(50)),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Assets::set_pool_cap(Origin::root(), TEST_ASSET, Some(65)));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::PoolCapSet(TEST_ASSET, Some(65)))
+		);
+
+		// far from the cap, the mint does not warn
+		mint_tokens_helper(1);
+		assert!(matches!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::Minted(..))
+		));
+
+		// the headroom drops to a tenth of the cap or less
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::PoolCapNearlyReached(TEST_ASSET, 5))
+		);
+
+		let payload = generate_mint_payload_helper(15);
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::PoolCapExceeded
+		);
+
+		// lifting the cap allows minting again
+		assert_ok!(Assets::set_pool_cap(Origin::root(), TEST_ASSET, None));
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+		assert_eq!(Assets::pool_balance(TEST_ASSET), 75);
+	});
+}
+
+#[test]
+fn mint_with_existing_coin_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::MantaCoinExist
+		);
+	});
+}
+
+#[test]
+fn mint_with_invalid_commitment_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let commit_param = CommitmentParam::deserialize(
+			Parameter {
+				data: &[0u8; 81664],
+			}
+			.data,
+		)
+		.unwrap();
+		let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+		let mut sk = [0u8; 32];
+		rng.fill_bytes(&mut sk);
+		let asset = MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &50).unwrap();
+		let payload = generate_mint_payload(&asset).unwrap();
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::MintFail
+		);
+	});
+}
+
+#[test]
+fn mint_with_hash_param_mismatch_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+
+		HashParamChecksum::put([3u8; 32]);
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::HashParamMismatch
+		);
+	});
+}
+
+#[test]
+fn mint_with_a_faulty_parameter_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let payload = generate_mint_payload_helper(50);
+		for (fault, error) in vec![
+			(Fault::HashParamDes, Error::<Test>::HashParamDesFail),
+			(
+				Fault::HashParamChecksum,
+				Error::<Test>::HashParamChecksumFail,
+			),
+			(Fault::CommitParamDes, Error::<Test>::CommitParamDesFail),
+			(
+				Fault::CommitParamChecksum,
+				Error::<Test>::CommitParamChecksumFail,
+			),
+			(Fault::LedgerUpdate, Error::<Test>::LedgerUpdateFail),
+		] {
+			inject_fault(fault);
+			assert_noop!(
+				Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+				error
+			);
+		}
+	});
+}
+
+#[test]
+fn mint_with_commit_param_mismatch_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+
+		CommitParamChecksum::put([3u8; 32]);
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::CommitParamMismatch
+		);
+	});
+}
+
+// Transfer tests:
+
+#[test]
+fn test_transfer_should_work() {
+	new_test_ext().execute_with(|| transfer_test_helper(1));
+}
+
+#[ignore]
+#[test]
+fn test_transfer_should_work_super_long() {
+	new_test_ext().execute_with(|| transfer_test_helper(400));
+}
+
+#[test]
+fn transferring_amount_below_available_balance_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 50));
+		assert_eq!(Assets::balance(1, TEST_ASSET), 50);
+		assert_eq!(Assets::balance(2, TEST_ASSET), 50);
+	});
+}
+
+#[test]
+fn transferring_amount_more_than_available_balance_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 50));
+		assert_eq!(Assets::balance(1, TEST_ASSET), 50);
+		assert_eq!(Assets::balance(2, TEST_ASSET), 50);
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 1, TEST_ASSET, 60),
+			Error::<Test>::BalanceLow
+		);
+	});
+}
+
+#[test]
+fn transferring_less_than_one_unit_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 0),
+			Error::<Test>::AmountZero
+		);
+	});
+}
+
+#[test]
+fn transferring_more_units_than_total_supply_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 101),
+			Error::<Test>::BalanceLow
+		);
+	});
+}
+
+#[test]
+fn transferring_with_hash_param_mismatch_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		HashParamChecksum::put([3u8; 32]);
+
+		// invoke the transfer event
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::HashParamMismatch
+		);
+	});
+}
+
+#[test]
+fn private_transfer_with_too_long_memo_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, Some(vec![0u8; MAX_MEMO_SIZE + 1])]
+			),
+			Error::<Test>::MemoTooLong
+		);
+	});
+}
+
+#[test]
+fn transferring_to_a_full_balance_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		<Balances<Test>>::insert(TEST_ASSET, 2, AssetBalance::MAX);
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 1),
+			Error::<Test>::Overflow
+		);
+	});
+}
+
+#[test]
+fn transferring_to_self_should_not_change_balance() {
+	new_test_ext().execute_with(|| {
+		initialize_test(AssetBalance::MAX);
+		assert_ok!(Assets::transfer_asset(
+			Origin::signed(1),
+			1,
+			TEST_ASSET,
+			AssetBalance::MAX
+		));
+		assert_eq!(Assets::balance(1, TEST_ASSET), AssetBalance::MAX);
+	});
+}
+
+#[test]
+fn transferring_whole_balance_should_remove_entry() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::transfer_asset(
+			Origin::signed(1),
+			2,
+			TEST_ASSET,
+			100
+		));
+		assert!(!<Balances<Test>>::contains_key(TEST_ASSET, 1));
+		assert_eq!(Assets::balance(2, TEST_ASSET), 100);
+	});
+}
+
+#[test]
+fn minting_whole_balance_should_remove_entry() {
+	new_test_ext().execute_with(|| {
+		initialize_test(50);
+		let payload = generate_mint_payload_helper(50);
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
+		assert!(!<Balances<Test>>::contains_key(TEST_ASSET, 1));
+	});
+}
+
+#[test]
+fn set_min_balance_requires_root() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_noop!(
+			Assets::set_min_balance(Origin::signed(1), TEST_ASSET, 10, DustPolicy::Burn),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Assets::set_min_balance(
+			Origin::root(),
+			TEST_ASSET,
+			10,
+			DustPolicy::Burn
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::MinBalanceSet(TEST_ASSET, 10, DustPolicy::Burn))
+		);
+		assert_eq!(
+			Assets::min_balance(TEST_ASSET),
+			Some((10, DustPolicy::Burn))
+		);
+		assert_ok!(Assets::set_min_balance(
+			Origin::root(),
+			TEST_ASSET,
+			0,
+			DustPolicy::Burn
+		));
+		assert_eq!(Assets::min_balance(TEST_ASSET), None);
+	});
+}
+
+#[test]
+fn transferring_with_dust_should_burn_it() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::set_min_balance(
+			Origin::root(),
+			TEST_ASSET,
+			10,
+			DustPolicy::Burn
+		));
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 95));
+		assert!(System::events().iter().any(|record| record.event
+			== Event::pallet_manta_pay(RawEvent::DustBurned(TEST_ASSET, 1, 5))));
+		assert!(!<Balances<Test>>::contains_key(TEST_ASSET, 1));
+		assert_eq!(Assets::balance(2, TEST_ASSET), 95);
+		assert_eq!(Assets::total_supply(TEST_ASSET), 95);
+	});
+}
+
+#[test]
+fn transferring_dust_to_the_receiver_should_not_burn_it() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::set_min_balance(
+			Origin::root(),
+			TEST_ASSET,
+			10,
+			DustPolicy::Burn
+		));
+		// only the remainder of the sender is burned, the credit fails
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 5),
+			Error::<Test>::BelowMinBalance
+		);
+		assert_ok!(Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 10));
+		assert_eq!(Assets::balance(2, TEST_ASSET), 10);
+		assert_eq!(Assets::total_supply(TEST_ASSET), 100);
+	});
+}
+
+#[test]
+fn burning_more_dust_than_the_total_supply_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::set_min_balance(
+			Origin::root(),
+			TEST_ASSET,
+			10,
+			DustPolicy::Burn
+		));
+		// a total supply below the dust left to account 1
+		TotalSupply::insert(TEST_ASSET, 4);
+
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 95),
+			Error::<Test>::Underflow
+		);
+	});
+}
+
+#[test]
+fn transferring_with_dust_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_ok!(Assets::set_min_balance(
+			Origin::root(),
+			TEST_ASSET,
+			10,
+			DustPolicy::Reject
+		));
+		// the sender would be left with dust
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 95),
+			Error::<Test>::BalanceBelowMinimum
+		);
+		// the receiver would only get dust
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 5),
+			Error::<Test>::BelowMinBalance
+		);
+		assert_ok!(Assets::transfer_asset(
+			Origin::signed(1),
+			2,
+			TEST_ASSET,
+			100
+		));
+	});
+}
+
+#[test]
+fn balances_should_never_wrap() {
+	new_test_ext().execute_with(|| {
+		initialize_test(AssetBalance::MAX);
+
+		let (commit_param, hash_param, pk, mut sk, mut proof_rng) = setup_params_for_reclaim();
+
+		// a random sequence of transfers, mints and reclaims, including amounts close
+		// to the bound, must preserve the total supply across the pool and the accounts
+		let accounts = [1u64, 2, 3, 4];
+		let mut rng = ChaCha20Rng::from_seed([7u8; 32]);
+		// the minted coins that are not reclaimed yet, small enough to be reclaimed
+		let mut coins = Vec::new();
+		let mut reclaims = 0;
+		for _ in 0usize..1000 {
+			let from = accounts[(rng.next_u32() % 4) as usize];
+			let to = accounts[(rng.next_u32() % 4) as usize];
+			let amount = match rng.next_u32() % 4 {
+				0 => ((rng.next_u64() as AssetBalance) << 64) | rng.next_u64() as AssetBalance,
+				1 => Assets::balance(from, TEST_ASSET),
+				2 => AssetBalance::MAX,
+				_ => rng.next_u32() as AssetBalance,
+			};
+			match rng.next_u32() % 8 {
+				0..=4 => {
+					let _ = Assets::transfer_asset(Origin::signed(from), to, TEST_ASSET, amount);
+				}
+				5 | 6 => {
+					rng.fill_bytes(&mut sk);
+					let coin =
+						MantaAsset::sample(&commit_param, &sk, &TEST_ASSET, &amount).unwrap();
+					let payload = generate_mint_payload(&coin).unwrap();
+					let minted =
+						Assets::mint_private_asset(Origin::signed(from), payload, None, None)
+							.is_ok();
+					// the reclaimed coin keeps 10 of the two spent coins
+					if minted && (10..=u32::MAX as AssetBalance).contains(&amount) {
+						coins.push(coin);
+					}
+				}
+				// a reclaim needs a proof, so only a few of them are run
+				_ if coins.len() >= 2 && reclaims < 4 => {
+					let (payload, ..) = prepare_reclaim_payload(
+						&coins,
+						&commit_param,
+						&hash_param,
+						&pk,
+						&mut sk,
+						&mut proof_rng,
+						0,
+						1,
+					);
+					assert_ok!(Assets::reclaim(
+						Origin::signed(from),
+						payload,
+						None,
+						None,
+						None
+					));
+					coins.drain(..2);
+					reclaims += 1;
+				}
+				_ => {}
+			}
+
+			let total = accounts
+				.iter()
+				.try_fold(PoolBalance::get(TEST_ASSET), |sum, account| {
+					sum.checked_add(Assets::balance(*account, TEST_ASSET))
+				});
+			assert_eq!(total, Some(TotalSupply::get(TEST_ASSET)));
+			assert_eq!(TotalSupply::get(TEST_ASSET), AssetBalance::MAX);
+		}
+		assert!(reclaims > 0);
+	});
+}
+
+#[test]
+fn transferring_without_init_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 101),
+			Error::<Test>::BasecoinNotInit
+		);
+	});
+}
+
+#[test]
+fn transferring_spent_coin_should_not_work_sender_1() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+		let iter = 1;
+		let size = iter << 1;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		for i in 0usize..iter {
+			let payload = prepare_private_transfer_payload(
+				&senders,
+				&commit_param,
+				&hash_param,
+				&pk,
+				&receivers_processed,
+				&mut rng,
+				i * 2,
+				i * 2 + 1,
+			);
+
+			assert_ok!(Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			));
+
+			assert_noop!(
+				Assets::private_transfer(
+					Origin::signed(1),
+					payload,
+					[None, None],
+					[None, None],
+					[None, None]
+				),
+				Error::<Test>::MantaCoinSpentSender1
+			);
+		}
+	});
+}
+
+#[test]
+fn transferring_existing_coins_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let iter = 2;
+		let size = iter << 1;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		for i in 0usize..iter {
+			let mut coin_shards = CoinShards::get();
+
+			// extract the receivers
+			let receiver_1 = receivers_processed[i * 2 + 1].clone();
+			let receiver_2 = receivers_processed[i * 2].clone();
+
+			let payload = prepare_private_transfer_payload(
+				&senders,
+				&commit_param,
+				&hash_param,
+				&pk,
+				&receivers_processed,
+				&mut rng,
+				i * 2,
+				i * 2 + 1,
+			);
+
+			if i == 0 {
+				coin_shards
+					.update(&receiver_1.utxo, hash_param.clone())
+					.unwrap();
+				CoinShards::put(coin_shards);
+
+				assert_noop!(
+					Assets::private_transfer(
+						Origin::signed(1),
+						payload,
+						[None, None],
+						[None, None],
+						[None, None]
+					),
+					Error::<Test>::MantaCoinExist
+				);
+			} else {
+				coin_shards
+					.update(&receiver_2.utxo, hash_param.clone())
+					.unwrap();
+				CoinShards::put(coin_shards);
+
+				assert_noop!(
+					Assets::private_transfer(
+						Origin::signed(1),
+						payload,
+						[None, None],
+						[None, None],
+						[None, None]
+					),
+					Error::<Test>::MantaCoinExist
+				);
+			}
+		}
+	});
+}
+
+#[test]
+fn transferring_spent_coin_should_not_work_sender_2() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 4;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		assert_ok!(Assets::private_transfer(
+			Origin::signed(1),
+			payload,
+			[None, None],
+			[None, None],
+			[None, None]
+		));
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			2,
+			0,
+		);
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::MantaCoinSpentSender2
+		);
+	});
+}
+
+#[test]
+fn transferring_the_same_coin_twice_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			0,
+		);
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::DuplicateVoidNumber
+		);
+		assert_noop!(
+			Assets::batch_private_transfer(Origin::signed(1), vec![batched(payload)]),
+			Error::<Test>::DuplicateVoidNumber
+		);
+	});
+}
+
+#[test]
+fn transferring_with_invalid_ledger_state_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 4;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+		data.sender_1.root = [5u8; 32];
+		let mut payload_with_bad_root = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload_with_bad_root.as_mut()).unwrap();
+
 		assert_noop!(
-			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 0),
-			Error::<Test>::AmountZero
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload_with_bad_root,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::InvalidLedgerState
+		);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			2,
+			3,
+		);
+
+		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+		data.sender_2.root = [5u8; 32];
+		let mut payload_with_bad_root = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload_with_bad_root.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload_with_bad_root,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::InvalidLedgerState
 		);
 	});
 }
 
 #[test]
-fn transferring_more_units_than_total_supply_should_not_work() {
+fn transferring_with_invalid_zkp_param_should_not_work() {
 	new_test_ext().execute_with(|| {
-		initialize_test(100);
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		let transfer_vk = VerificationKey { data: &[0u8; 2312] };
+		let transfer_key_digest = transfer_vk.get_checksum().unwrap();
+		TransferZKPKeyChecksum::put(transfer_key_digest);
 		assert_noop!(
-			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 101),
-			Error::<Test>::BalanceLow
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::ZkpKeyMismatch
 		);
 	});
 }
 
 #[test]
-fn transferring_with_hash_param_mismatch_should_not_work() {
+fn init_should_cache_prepared_keys() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_eq!(
+			Assets::prepared_transfer_vk(),
+			Some((
+				Assets::transfer_zkp_vk_checksum(),
+				Groth16::prepare_transfer_key().unwrap()
+			))
+		);
+		assert_eq!(
+			Assets::prepared_reclaim_vk(),
+			Some((
+				Assets::reclaim_zkp_vk_checksum(),
+				Groth16::prepare_reclaim_key().unwrap()
+			))
+		);
+	});
+}
+
+#[test]
+fn transferring_without_a_prepared_key_should_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
+		let prepared_key = PreparedTransferKey::get();
+		PreparedTransferKey::kill();
 
-		let payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
-		HashParamChecksum::put([3u8; 32]);
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		assert_ok!(Assets::private_transfer(
+			Origin::signed(1),
+			payload,
+			[None, None],
+			[None, None],
+			[None, None]
+		));
+
+		// the key is prepared again, and cached for the next transfers
+		assert_eq!(PreparedTransferKey::get(), prepared_key);
+	});
+}
+
+#[test]
+fn transferring_with_a_faulty_key_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+		PreparedTransferKey::kill();
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		// the key is not cached, so it is checked and prepared again
+		for (fault, error) in vec![
+			(Fault::ZkpKeyChecksum, Error::<Test>::ZkpKeyChecksumFail),
+			(Fault::ZkpKeyPrepare, Error::<Test>::ZkpKeyPrepareFail),
+		] {
+			inject_fault(fault);
+			assert_noop!(
+				Assets::private_transfer(
+					Origin::signed(1),
+					payload,
+					[None, None],
+					[None, None],
+					[None, None]
+				),
+				error
+			);
+		}
+	});
+}
+
+#[test]
+fn transferring_with_zkp_verification_fail_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+		// negate A: the proof is still well formed, but does not verify
+		data.proof[47] ^= 0x80;
+		let mut payload_with_bad_proof = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload_with_bad_proof.as_mut()).unwrap();
 
-		// invoke the transfer event
 		assert_noop!(
-			Assets::private_transfer(Origin::signed(1), payload),
-			Error::<Test>::ParamFail
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload_with_bad_proof,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::ZkpVerificationFail
 		);
 	});
 }
 
 #[test]
-fn transferring_without_init_should_not_work() {
+fn transferring_to_duplicate_receivers_should_not_work() {
 	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+
+		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+		data.receiver_2 = data.receiver_1.clone();
+		let mut payload_with_duplicates = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload_with_duplicates.as_mut()).unwrap();
+
+		// the failed call must leave the coin list and the void numbers untouched
 		assert_noop!(
-			Assets::transfer_asset(Origin::signed(1), 2, TEST_ASSET, 101),
-			Error::<Test>::BasecoinNotInit
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload_with_duplicates,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::DuplicateCoin
 		);
 	});
 }
 
 #[test]
-fn transferring_spent_coin_should_not_work_sender_1() {
+fn batch_private_transfer_should_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
 		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
-		let iter = 1;
-		let size = iter << 1;
+
+		let size = 4;
 		let senders = mint_tokens_helper(size);
 
 		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
 
-		for i in 0usize..iter {
-			let payload = prepare_private_transfer_payload(
+		let payloads = vec![
+			prepare_private_transfer_payload(
 				&senders,
 				&commit_param,
 				&hash_param,
 				&pk,
 				&receivers_processed,
 				&mut rng,
-				i * 2,
-				i * 2 + 1,
-			);
-
-			assert_ok!(Assets::private_transfer(Origin::signed(1), payload));
-
-			assert_noop!(
-				Assets::private_transfer(Origin::signed(1), payload),
-				Error::<Test>::MantaCoinSpent
-			);
-		}
+				0,
+				1,
+			),
+			prepare_private_transfer_payload(
+				&senders,
+				&commit_param,
+				&hash_param,
+				&pk,
+				&receivers_processed,
+				&mut rng,
+				2,
+				3,
+			),
+		];
+
+		// the first transfer carries a view tag and a memo for its second receiver
+		let mut transfers: Vec<_> = payloads.into_iter().map(batched).collect();
+		let memo = b"invoice #2".to_vec();
+		transfers[0].view_tags = [None, Some(3)];
+		transfers[0].memos = [None, Some(memo.clone())];
+
+		assert_ok!(Assets::batch_private_transfer(Origin::signed(1), transfers));
+		assert_eq!(Assets::void_number_count(), 4);
+		assert_eq!(Assets::coin_count(), 8);
+		assert_eq!(Assets::note_count(), 8);
+		assert_eq!(Assets::view_tag(5), Some(3));
+		assert_eq!(Assets::memo(5), Some(memo));
+		assert!((0..8)
+			.filter(|note_index| *note_index != 5)
+			.all(|note_index| Assets::view_tag(note_index).is_none()
+				&& Assets::memo(note_index).is_none()));
+		assert_eq!(
+			last_event(),
+			Event::from(RawEvent::PrivateTransferred(
+				1,
+				[senders[2].void_number, senders[3].void_number],
+				[receivers_processed[3].utxo, receivers_processed[2].utxo],
+				[
+					receivers_processed[3].utxo[0],
+					receivers_processed[2].utxo[0]
+				],
+				[6, 7],
+			))
+		);
 	});
 }
 
+/// Print `precomputed_coins::TEST_BATCH` for batches of up to `MAX_BATCH_SIZE` transfers, with
+/// `cargo test print_batch_payloads -- --ignored --nocapture`.
+/// The mints are those of `mint_tokens_helper`, so the first transfer spends the coins of
+/// `TEST_MINT_10_PAYLOAD` and `TEST_MINT_11_PAYLOAD`.
+#[ignore]
 #[test]
-fn transferring_existing_coins_should_not_work() {
+fn print_batch_payloads() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
 		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
 
-		let iter = 2;
-		let size = iter << 1;
+		let size = 2 * MAX_BATCH_SIZE;
 		let senders = mint_tokens_helper(size);
 
 		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
 
-		for i in 0usize..iter {
-			let mut coin_shards = CoinShards::get();
-
-			// extract the receivers
-			let receiver_1 = receivers_processed[i * 2 + 1].clone();
-			let receiver_2 = receivers_processed[i * 2].clone();
-
+		println!("pub const TEST_BATCH: &[(");
+		println!("\t[[u8; MINT_PAYLOAD_SIZE]; 2],");
+		println!("\t[u8; PRIVATE_TRANSFER_PAYLOAD_SIZE],");
+		println!(")] = &[");
+		for i in 0usize..MAX_BATCH_SIZE {
+			let mints = [
+				generate_mint_payload(&senders[2 * i]).unwrap(),
+				generate_mint_payload(&senders[2 * i + 1]).unwrap(),
+			];
 			let payload = prepare_private_transfer_payload(
 				&senders,
 				&commit_param,
@@ -439,37 +2255,47 @@ fn transferring_existing_coins_should_not_work() {
 				&pk,
 				&receivers_processed,
 				&mut rng,
-				i * 2,
-				i * 2 + 1,
+				2 * i,
+				2 * i + 1,
 			);
+			println!(
+				"\t([{:?}, {:?}], {:?}),",
+				mints[0].to_vec(),
+				mints[1].to_vec(),
+				payload.to_vec()
+			);
+		}
+		println!("];");
+	});
+}
 
-			if i == 0 {
-				coin_shards
-					.update(&receiver_1.utxo, hash_param.clone())
-					.unwrap();
-				CoinShards::put(coin_shards);
-
-				assert_noop!(
-					Assets::private_transfer(Origin::signed(1), payload),
-					Error::<Test>::MantaCoinExist
-				);
-			} else {
-				coin_shards
-					.update(&receiver_2.utxo, hash_param.clone())
-					.unwrap();
-				CoinShards::put(coin_shards);
+#[test]
+fn batch_private_transfer_with_bad_sizes_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
 
-				assert_noop!(
-					Assets::private_transfer(Origin::signed(1), payload),
-					Error::<Test>::MantaCoinExist
-				);
-			}
-		}
+		assert_noop!(
+			Assets::batch_private_transfer(Origin::signed(1), vec![]),
+			Error::<Test>::EmptyBatch
+		);
+		assert_noop!(
+			Assets::batch_private_transfer(
+				Origin::signed(1),
+				vec![batched(precomputed_coins::TEST_TRANSFER_PAYLOAD); MAX_BATCH_SIZE + 1]
+			),
+			Error::<Test>::BatchTooLarge
+		);
+		let mut transfer = batched(precomputed_coins::TEST_TRANSFER_PAYLOAD);
+		transfer.memos[1] = Some(vec![0u8; MAX_MEMO_SIZE + 1]);
+		assert_noop!(
+			Assets::batch_private_transfer(Origin::signed(1), vec![transfer]),
+			Error::<Test>::MemoTooLong
+		);
 	});
 }
 
 #[test]
-fn transferring_spent_coin_should_not_work_sender_2() {
+fn batch_private_transfer_with_a_bad_proof_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
@@ -490,10 +2316,7 @@ fn transferring_spent_coin_should_not_work_sender_2() {
 			0,
 			1,
 		);
-
-		assert_ok!(Assets::private_transfer(Origin::signed(1), payload));
-
-		let payload = prepare_private_transfer_payload(
+		let other_payload = prepare_private_transfer_payload(
 			&senders,
 			&commit_param,
 			&hash_param,
@@ -501,18 +2324,29 @@ fn transferring_spent_coin_should_not_work_sender_2() {
 			&receivers_processed,
 			&mut rng,
 			2,
-			0,
+			3,
 		);
 
+		// swap the proofs of the two transfers: each proof is valid, but not for its transfer
+		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+		let other_data = PrivateTransferData::deserialize(other_payload.as_ref()).unwrap();
+		data.proof = other_data.proof;
+		let mut payload_with_bad_proof = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload_with_bad_proof.as_mut()).unwrap();
+
+		// none of the transfers is applied
 		assert_noop!(
-			Assets::private_transfer(Origin::signed(1), payload),
-			Error::<Test>::MantaCoinSpent
+			Assets::batch_private_transfer(
+				Origin::signed(1),
+				vec![batched(other_payload), batched(payload_with_bad_proof)]
+			),
+			Error::<Test>::ZkpVerificationFail
 		);
 	});
 }
 
 #[test]
-fn transferring_with_invalid_ledger_state_should_not_work() {
+fn batch_private_transfer_with_double_spend_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
@@ -533,160 +2367,265 @@ fn transferring_with_invalid_ledger_state_should_not_work() {
 			0,
 			1,
 		);
-
-		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
-		data.sender_1.root = [5u8; 32];
-		let mut payload_with_bad_root = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
-		data.serialize(payload_with_bad_root.as_mut()).unwrap();
-
-		assert_noop!(
-			Assets::private_transfer(Origin::signed(1), payload_with_bad_root),
-			Error::<Test>::InvalidLedgerState
-		);
-
-		let payload = prepare_private_transfer_payload(
+		let double_spend = prepare_private_transfer_payload(
 			&senders,
 			&commit_param,
 			&hash_param,
 			&pk,
 			&receivers_processed,
 			&mut rng,
+			0,
 			2,
-			3,
 		);
 
-		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
-		data.sender_2.root = [5u8; 32];
-		let mut payload_with_bad_root = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
-		data.serialize(payload_with_bad_root.as_mut()).unwrap();
+		assert_noop!(
+			Assets::batch_private_transfer(
+				Origin::signed(1),
+				vec![batched(payload), batched(double_spend)]
+			),
+			Error::<Test>::MantaCoinSpentSender1
+		);
+	});
+}
+
+#[test]
+fn batch_private_transfer_when_paused_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
 
+		assert_ok!(Assets::pause(
+			Origin::root(),
+			PausableOperation::PrivateTransfer,
+			None
+		));
 		assert_noop!(
-			Assets::private_transfer(Origin::signed(1), payload_with_bad_root),
-			Error::<Test>::InvalidLedgerState
+			Assets::batch_private_transfer(
+				Origin::signed(1),
+				vec![batched(precomputed_coins::TEST_TRANSFER_PAYLOAD)]
+			),
+			Error::<Test>::OperationPaused
 		);
 	});
 }
 
+// Reclaim tests:
+
 #[test]
-fn transferring_with_invalid_zkp_param_should_not_work() {
+fn test_reclaim_should_work() {
+	new_test_ext().execute_with(|| reclaim_test_helper(1));
+}
+
+#[ignore]
+#[test]
+fn test_reclaim_should_work_super_long() {
+	new_test_ext().execute_with(|| reclaim_test_helper(400));
+}
+
+#[test]
+fn reclaim_without_init_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let payload = [0u8; RECLAIM_PAYLOAD_SIZE];
+
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::BasecoinNotInit
+		);
+	});
+}
+
+#[test]
+fn reclaim_with_too_long_memo_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
-		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+		let payload = [0u8; RECLAIM_PAYLOAD_SIZE];
+		assert_noop!(
+			Assets::reclaim(
+				Origin::signed(1),
+				payload,
+				None,
+				None,
+				Some(vec![0u8; MAX_MEMO_SIZE + 1])
+			),
+			Error::<Test>::MemoTooLong
+		);
+	});
+}
+
+#[test]
+fn reclaim_with_hash_param_mismatch_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let payload = [0u8; RECLAIM_PAYLOAD_SIZE];
+		HashParamChecksum::put([3u8; 32]);
+
+		// invoke the transfer event
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::HashParamMismatch
+		);
+	});
+}
+
+#[test]
+fn reclaim_with_overdrawn_pool_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
 
 		let size = 2;
 		let senders = mint_tokens_helper(size);
 
-		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
-
-		let payload = prepare_private_transfer_payload(
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
 			&senders,
 			&commit_param,
 			&hash_param,
 			&pk,
-			&receivers_processed,
+			&mut sk,
 			&mut rng,
 			0,
 			1,
 		);
 
-		let transfer_vk = VerificationKey { data: &[0u8; 2312] };
-		let transfer_key_digest = transfer_vk.get_checksum().unwrap();
-		TransferZKPKeyChecksum::put(transfer_key_digest);
+		assert_ok!(Assets::reclaim(
+			Origin::signed(1),
+			payload,
+			None,
+			None,
+			None
+		));
+
 		assert_noop!(
-			Assets::private_transfer(Origin::signed(1), payload),
-			Error::<Test>::ZkpParamFail
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::PoolOverdrawn
 		);
 	});
 }
 
 #[test]
-fn transferring_with_zkp_verification_fail_should_not_work() {
+fn reclaim_to_a_full_balance_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
-		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_transferring();
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
 
 		let size = 2;
 		let senders = mint_tokens_helper(size);
 
-		let (_, receivers_processed) = build_receivers(&commit_param, &mut sk, &mut rng, size);
-
-		let payload = prepare_private_transfer_payload(
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
 			&senders,
 			&commit_param,
 			&hash_param,
 			&pk,
-			&receivers_processed,
+			&mut sk,
 			&mut rng,
 			0,
 			1,
 		);
 
-		let mut data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
-		data.proof = [0u8; 192];
-		let mut payload_with_bad_proof = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
-		data.serialize(payload_with_bad_proof.as_mut()).unwrap();
-
+		// the new balance of the origin overflows, which is checked before any write
+		<Balances<Test>>::insert(TEST_ASSET, 1, AssetBalance::MAX);
 		assert_noop!(
-			Assets::private_transfer(Origin::signed(1), payload_with_bad_proof),
-			Error::<Test>::ZkpVerificationFail
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::Overflow
 		);
 	});
 }
 
-// Reclaim tests:
-
 #[test]
-fn test_reclaim_should_work() {
-	new_test_ext().execute_with(|| reclaim_test_helper(1));
-}
+fn reclaim_failing_after_the_ledger_writes_should_not_change_storage() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
 
-#[ignore]
-#[test]
-fn test_reclaim_should_work_super_long() {
-	new_test_ext().execute_with(|| reclaim_test_helper(400));
-}
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
 
-#[test]
-fn reclaim_without_init_should_not_work() {
-	new_test_ext().execute_with(|| {
-		let payload = [0u8; RECLAIM_PAYLOAD_SIZE];
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&mut sk,
+			&mut rng,
+			0,
+			1,
+		);
 
+		// the void numbers, the coin, the pool balance, the note and the memo are
+		// written before the balance of the origin, which is then rejected as a credit
+		// below the minimum balance;
+		// `assert_noop` checks that the storage root is unchanged
+		MinBalances::insert(TEST_ASSET, (AssetBalance::MAX, DustPolicy::Reject));
+		let vn_list = VNList::get();
+		let coin_count = Assets::coin_count();
+		let note_count = Assets::note_count();
+		let pool_balance = Assets::pool_balance(TEST_ASSET);
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::BasecoinNotInit
+			Assets::reclaim(
+				Origin::signed(1),
+				payload,
+				Some(0),
+				None,
+				Some(b"memo".to_vec())
+			),
+			Error::<Test>::BelowMinBalance
 		);
+		assert_eq!(VNList::get(), vn_list);
+		assert_eq!(Assets::coin_count(), coin_count);
+		assert_eq!(Assets::note_count(), note_count);
+		assert_eq!(Assets::pool_balance(TEST_ASSET), pool_balance);
+		assert_eq!(Assets::memo(note_count as u64), None);
 	});
 }
 
 #[test]
-fn reclaim_with_hash_param_mismatch_should_not_work() {
+fn reclaim_without_auditor_note_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
-		let payload = [0u8; RECLAIM_PAYLOAD_SIZE];
-		HashParamChecksum::put([3u8; 32]);
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
 
-		// invoke the transfer event
+		let size = 2;
+		let senders = mint_tokens_helper(size);
+
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&mut sk,
+			&mut rng,
+			0,
+			1,
+		);
+
+		assert_ok!(Assets::set_auditor_key(
+			Origin::root(),
+			TEST_ASSET,
+			Some([9u8; 32])
+		));
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::ParamFail
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::AuditorNoteMissing
 		);
 	});
 }
 
 #[test]
-fn reclaim_with_overdrawn_pool_should_not_work() {
+fn reclaim_over_reclaim_limit_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
 		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
 
-		let size = 2;
+		let size = 4;
 		let senders = mint_tokens_helper(size);
 
-		let (payload, _, _, _, _) = prepare_reclaim_payload(
+		let (payload, _, _, reclaim_value, _) = prepare_reclaim_payload(
 			&senders,
 			&commit_param,
 			&hash_param,
@@ -697,12 +2636,176 @@ fn reclaim_with_overdrawn_pool_should_not_work() {
 			1,
 		);
 
-		assert_ok!(Assets::reclaim(Origin::signed(1), payload));
+		assert_ok!(Assets::set_reclaim_limit(
+			Origin::root(),
+			TEST_ASSET,
+			Some(ReclaimLimit {
+				amount: reclaim_value - 1,
+				period: 10,
+			})
+		));
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::ReclaimLimitExceeded
+		);
+
+		// the first reclaim exhausts the limit of the window
+		assert_ok!(Assets::set_reclaim_limit(
+			Origin::root(),
+			TEST_ASSET,
+			Some(ReclaimLimit {
+				amount: reclaim_value,
+				period: 10,
+			})
+		));
+		assert_ok!(Assets::reclaim(
+			Origin::signed(1),
+			payload,
+			None,
+			None,
+			None
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::ReclaimLimitNearlyReached(TEST_ASSET, 0, 1))
+		);
+
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&mut sk,
+			&mut rng,
+			2,
+			3,
+		);
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::ReclaimLimitExceeded
+		);
+	});
+}
+
+#[test]
+fn reclaim_limit_should_hold_over_any_window() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
+
+		let size = 6;
+		let senders = mint_tokens_helper(size);
+
+		// the i-th reclaim spends the coins 2i and 2i + 1, less the 10 of the new coin
+		let values: Vec<AssetBalance> = (0..3)
+			.map(|i| senders[2 * i].priv_info.value + senders[2 * i + 1].priv_info.value - 10)
+			.collect();
+		let mut reclaim = |i: usize| {
+			let (payload, _, _, reclaim_value, _) = prepare_reclaim_payload(
+				&senders,
+				&commit_param,
+				&hash_param,
+				&pk,
+				&mut sk,
+				&mut rng,
+				2 * i,
+				2 * i + 1,
+			);
+			assert_eq!(reclaim_value, values[i]);
+			Assets::reclaim(Origin::signed(1), payload, None, None, None)
+		};
+
+		assert_ok!(Assets::set_reclaim_limit(
+			Origin::root(),
+			TEST_ASSET,
+			Some(ReclaimLimit {
+				amount: values[0] + values[1],
+				period: 10,
+			})
+		));
+		assert_ok!(reclaim(0));
+		assert!(matches!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::PrivateReclaimed(..))
+		));
+
+		// the last block of the window of the first reclaim reaches the limit
+		System::set_block_number(10);
+		assert_ok!(reclaim(1));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::ReclaimLimitNearlyReached(TEST_ASSET, 0, 1))
+		);
+
+		// the first reclaim has expired, but the second one still counts
+		System::set_block_number(11);
+		assert!(values[1] + values[2] > values[0] + values[1]);
+		assert_noop!(reclaim(2), Error::<Test>::ReclaimLimitExceeded);
+		assert_eq!(
+			Assets::reclaim_history(TEST_ASSET),
+			vec![(1, values[0]), (10, values[1])]
+		);
+
+		System::set_block_number(20);
+		assert_ok!(reclaim(2));
+		assert_eq!(Assets::reclaim_history(TEST_ASSET), vec![(20, values[2])]);
+	});
+}
+
+#[test]
+fn reclaim_history_should_move_the_oldest_amount_to_the_next_block() {
+	let last_block = MAX_RECLAIM_HISTORY as u64 + 1;
+	let mut history = Vec::new();
+	for block in 1..=last_block {
+		Assets::record_reclaim(&mut history, block, 1);
+	}
+	// a second reclaim in the same block is recorded with the first one
+	Assets::record_reclaim(&mut history, last_block, 1);
+
+	assert_eq!(history.len(), MAX_RECLAIM_HISTORY);
+	assert_eq!(history.first(), Some(&(2, 2)));
+	assert_eq!(history.last(), Some(&(last_block, 2)));
+}
 
+#[test]
+fn set_reclaim_limit_should_check_origin_and_period() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		let reclaim_limit = ReclaimLimit {
+			amount: 10,
+			period: 1,
+		};
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::PoolOverdrawn
+			Assets::set_reclaim_limit(Origin::signed(1), TEST_ASSET, Some(reclaim_limit.clone())),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Assets::set_reclaim_limit(
+				Origin::root(),
+				TEST_ASSET,
+				Some(ReclaimLimit {
+					amount: 10,
+					period: 0,
+				})
+			),
+			Error::<Test>::ReclaimPeriodZero
+		);
+		assert_ok!(Assets::set_reclaim_limit(
+			Origin::root(),
+			TEST_ASSET,
+			Some(reclaim_limit.clone())
+		));
+		assert_eq!(
+			last_event(),
+			Event::pallet_manta_pay(RawEvent::ReclaimLimitSet(
+				TEST_ASSET,
+				Some(reclaim_limit.clone())
+			))
 		);
+		assert_eq!(Assets::reclaim_limit(TEST_ASSET), Some(reclaim_limit));
+		assert_ok!(Assets::set_reclaim_limit(Origin::root(), TEST_ASSET, None));
+		assert_eq!(Assets::reclaim_limit(TEST_ASSET), None);
 	});
 }
 
@@ -724,10 +2827,32 @@ fn reclaim_spent_coin_should_not_work() {
 			&mut sk,
 			&mut rng,
 			0,
-			1,
+			1,
+		);
+
+		assert_ok!(Assets::reclaim(
+			Origin::signed(1),
+			payload,
+			None,
+			None,
+			None
+		));
+
+		let (payload, _, _, _, _) = prepare_reclaim_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&mut sk,
+			&mut rng,
+			0,
+			2,
 		);
 
-		assert_ok!(Assets::reclaim(Origin::signed(1), payload));
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::MantaCoinSpentSender1
+		);
 
 		let (payload, _, _, _, _) = prepare_reclaim_payload(
 			&senders,
@@ -736,14 +2861,26 @@ fn reclaim_spent_coin_should_not_work() {
 			&pk,
 			&mut sk,
 			&mut rng,
+			3,
 			0,
-			2,
 		);
 
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::MantaCoinSpent
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::MantaCoinSpentSender2
 		);
+	});
+}
+
+#[test]
+fn reclaim_the_same_coin_twice_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, mut sk, mut rng) = setup_params_for_reclaim();
+
+		let size = 2;
+		let senders = mint_tokens_helper(size);
 
 		let (payload, _, _, _, _) = prepare_reclaim_payload(
 			&senders,
@@ -752,19 +2889,19 @@ fn reclaim_spent_coin_should_not_work() {
 			&pk,
 			&mut sk,
 			&mut rng,
-			3,
+			0,
 			0,
 		);
 
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::MantaCoinSpent
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::DuplicateVoidNumber
 		);
 	});
 }
 
 #[test]
-fn reclaim_spent_coin_should_not_work_2() {
+fn reclaim_existing_coin_should_not_work() {
 	new_test_ext().execute_with(|| {
 		initialize_test(10_000_000);
 
@@ -791,8 +2928,8 @@ fn reclaim_spent_coin_should_not_work_2() {
 		CoinShards::put(coin_shards);
 
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::MantaCoinSpent
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::MantaCoinExist
 		);
 	});
 }
@@ -823,8 +2960,8 @@ fn reclaim_with_invalid_zkp_param_should_not_work() {
 		let reclaim_key_digest = reclaim_vk.get_checksum().unwrap();
 		ReclaimZKPKeyChecksum::put(reclaim_key_digest);
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload),
-			Error::<Test>::ZkpParamFail
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::ZkpKeyMismatch
 		);
 	});
 }
@@ -856,7 +2993,7 @@ fn reclaim_with_invalid_ledger_state_should_not_work() {
 		data.serialize(payload_with_bad_root.as_mut()).unwrap();
 
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload_with_bad_root),
+			Assets::reclaim(Origin::signed(1), payload_with_bad_root, None, None, None),
 			Error::<Test>::InvalidLedgerState
 		);
 
@@ -877,7 +3014,7 @@ fn reclaim_with_invalid_ledger_state_should_not_work() {
 		data.serialize(payload_with_bad_root.as_mut()).unwrap();
 
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload_with_bad_root),
+			Assets::reclaim(Origin::signed(1), payload_with_bad_root, None, None, None),
 			Error::<Test>::InvalidLedgerState
 		);
 	});
@@ -905,19 +3042,604 @@ fn reclaim_with_zkp_verification_fail_should_not_work() {
 		);
 
 		let mut data = ReclaimData::deserialize(payload.as_ref()).unwrap();
-		data.proof = [6u8; 192];
+		// negate A: the proof is still well formed, but does not verify
+		data.proof[47] ^= 0x80;
 		let mut payload_with_bad_proof = [0u8; RECLAIM_PAYLOAD_SIZE];
 		data.serialize(payload_with_bad_proof.as_mut()).unwrap();
 
 		assert_noop!(
-			Assets::reclaim(Origin::signed(1), payload_with_bad_proof),
+			Assets::reclaim(Origin::signed(1), payload_with_bad_proof, None, None, None),
 			Error::<Test>::ZkpVerificationFail
 		);
 	});
 }
 
+// Auditor tests:
+
+#[test]
+fn set_auditor_key_requires_root() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+		assert_noop!(
+			Assets::set_auditor_key(Origin::signed(1), TEST_ASSET, Some([9u8; 32])),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Assets::set_auditor_key(
+			Origin::root(),
+			TEST_ASSET,
+			Some([9u8; 32])
+		));
+		assert_eq!(AuditorKeys::get(TEST_ASSET), Some([9u8; 32]));
+		assert_ok!(Assets::set_auditor_key(Origin::root(), TEST_ASSET, None));
+		assert_eq!(AuditorKeys::get(TEST_ASSET), None);
+	});
+}
+
+#[test]
+fn set_auditor_key_without_init_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Assets::set_auditor_key(Origin::root(), TEST_ASSET, Some([9u8; 32])),
+			Error::<Test>::BasecoinNotInit
+		);
+	});
+}
+
+#[test]
+fn auditor_should_decrypt_asset_history() {
+	new_test_ext().execute_with(|| {
+		initialize_test(100);
+
+		let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data).unwrap();
+		let mut rng = ChaCha20Rng::from_seed([9u8; 32]);
+		let auditor =
+			MantaAssetFullReceiver::sample(&commit_param, &[9u8; 32], &TEST_ASSET, &()).unwrap();
+		assert_ok!(Assets::set_auditor_key(
+			Origin::root(),
+			TEST_ASSET,
+			Some([9u8; 32])
+		));
+
+		let payload = generate_mint_payload_helper(50);
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::AuditorNoteMissing
+		);
+
+		let plaintext = [
+			TEST_ASSET.to_le_bytes().as_ref(),
+			(50 as AssetBalance).to_le_bytes().as_ref(),
+		]
+		.concat();
+		let auditor_note =
+			<MantaCrypto as Ecies>::encrypt(&auditor.shielded_address.ecpk, &plaintext, &mut rng)
+				.unwrap();
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			Some(auditor_note)
+		));
+
+		assert!(Assets::auditor_note(0).is_some());
+		let auditor = Auditor::new(auditor.spending_info.ecsk);
+		assert_eq!(
+			auditor.history(TEST_ASSET, AuditorNotes::iter()),
+			vec![(0, 50)]
+		);
+		assert!(auditor
+			.history(TEST_ASSET + 1, AuditorNotes::iter())
+			.is_empty());
+	});
+}
+
+// Wallet tests:
+
+#[test]
+fn decoding_a_malformed_note_should_not_panic() {
+	let plaintext = [
+		TEST_ASSET.to_le_bytes().as_ref(),
+		(50 as AssetBalance).to_le_bytes().as_ref(),
+	]
+	.concat();
+	assert_eq!(decode_note(&plaintext), Some((TEST_ASSET, 50)));
+
+	// anyone can store a note, so the plaintext can have any size
+	assert_eq!(decode_note(&[]), None);
+	assert_eq!(decode_note(&plaintext[..plaintext.len() - 1]), None);
+	assert_eq!(decode_note(&[plaintext.as_slice(), &[0u8]].concat()), None);
+}
+
+#[test]
+fn wallet_sync_should_recover_unspent_coins() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, _, mut rng) = setup_params_for_transferring();
+
+		let size = 4;
+		let senders = mint_tokens_helper(size);
+		let sender_seeds = mint_seeds_helper(size);
+
+		// receivers with known seeds
+		let mut receiver_seeds = Vec::new();
+		let mut receivers_processed = Vec::new();
+		for i in 0usize..2 {
+			let mut seed = [0u8; 32];
+			rng.fill_bytes(&mut seed);
+			let receiver_full =
+				MantaAssetFullReceiver::sample(&commit_param, &seed, &TEST_ASSET, &()).unwrap();
+			let receiver = receiver_full
+				.shielded_address
+				.process(&(i as AssetBalance + 10), &mut rng)
+				.unwrap();
+			receiver_seeds.push(seed);
+			receivers_processed.push(receiver);
+		}
+
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+		// receiver 1 gets a memo
+		let memo = b"invoice #1".to_vec();
+		assert_ok!(Assets::private_transfer(
+			Origin::signed(1),
+			payload,
+			[None, None],
+			[None, None],
+			[Some(memo.clone()), None]
+		));
+
+		let mut wallet = Wallet::new(commit_param.clone());
+		for seed in sender_seeds.iter().chain(receiver_seeds.iter()) {
+			wallet.add_key(*seed, TEST_ASSET).unwrap();
+		}
+
+		let notes = ledger_notes_helper();
+		wallet.sync(notes.iter().map(as_ledger_note), &VNList::get());
+
+		// senders 0 and 1 are spent
+		let mut expected = vec![
+			senders[2].utxo,
+			senders[3].utxo,
+			receivers_processed[0].utxo,
+			receivers_processed[1].utxo,
+		];
+		let mut recovered: Vec<[u8; 32]> = wallet.coins().iter().map(|c| c.asset.utxo).collect();
+		expected.sort();
+		recovered.sort();
+		assert_eq!(recovered, expected);
+		assert_eq!(wallet.balance(TEST_ASSET), 12 + 13 + 10 + 11);
+
+		// the memo is returned with the coin
+		for coin in wallet.coins() {
+			if coin.asset.utxo == receivers_processed[1].utxo {
+				assert_eq!(coin.memo, Some(memo.clone()));
+			} else {
+				assert_eq!(coin.memo, None);
+			}
+		}
+
+		// a wallet with unrelated keys does not recover anything
+		let mut other_wallet = Wallet::new(commit_param);
+		other_wallet.add_key([42u8; 32], TEST_ASSET).unwrap();
+		other_wallet.sync(notes.iter().map(as_ledger_note), &VNList::get());
+		assert!(other_wallet.coins().is_empty());
+	});
+}
+
+#[test]
+fn wallet_scan_should_skip_notes_with_mismatched_view_tag() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data).unwrap();
+		let senders = mint_tokens_helper(1);
+		let seed = mint_seeds_helper(1)[0];
+		let ecsk = MantaAssetFullReceiver::sample(&commit_param, &seed, &TEST_ASSET, &())
+			.unwrap()
+			.spending_info
+			.ecsk;
+		let note = EncValueList::get()[0].clone();
+		let view_tag = derive_view_tag(&<MantaCrypto as Ecies>::shared_secret(&ecsk, &note));
+
+		let mut wallet = Wallet::new(commit_param.clone());
+		wallet.add_key(seed, TEST_ASSET).unwrap();
+		let mut ledger_note = LedgerNote {
+			cm: &senders[0].utxo,
+			note: &note,
+			view_tag: Some(view_tag.wrapping_add(1)),
+			memo: None,
+		};
+		assert!(!wallet.scan(ledger_note));
+		assert!(wallet.coins().is_empty());
+		ledger_note.view_tag = Some(view_tag);
+		assert!(wallet.scan(ledger_note));
+		assert_eq!(wallet.coins().len(), 1);
+	});
+}
+
+#[test]
+fn wallet_should_find_a_transfer_with_the_sender_view_tags() {
+	new_test_ext().execute_with(|| {
+		initialize_test(10_000_000);
+
+		let (commit_param, hash_param, pk, _, mut rng) = setup_params_for_transferring();
+		let senders = mint_tokens_helper(2);
+
+		// the sender encrypts the notes with keys drawn from a `ViewTagRng`
+		let mut tag_rng = ViewTagRng::new(ChaCha20Rng::from_seed([7u8; 32]));
+		let mut receiver_seeds = Vec::new();
+		let mut receiver_pks = Vec::new();
+		let mut receivers_processed = Vec::new();
+		for i in 0usize..2 {
+			let mut seed = [0u8; 32];
+			rng.fill_bytes(&mut seed);
+			let receiver_full =
+				MantaAssetFullReceiver::sample(&commit_param, &seed, &TEST_ASSET, &()).unwrap();
+			let receiver = receiver_full
+				.shielded_address
+				.process(&(i as AssetBalance + 10), &mut tag_rng)
+				.unwrap();
+			receiver_seeds.push(seed);
+			receiver_pks.push(*receiver_full.shielded_address.ecpk.as_bytes());
+			receivers_processed.push(receiver);
+		}
+
+		// senders 0 and 1 pay receivers 1 and 0
+		let payload = prepare_private_transfer_payload(
+			&senders,
+			&commit_param,
+			&hash_param,
+			&pk,
+			&receivers_processed,
+			&mut rng,
+			0,
+			1,
+		);
+		let data = PrivateTransferData::deserialize(payload.as_ref()).unwrap();
+		let view_tags = [
+			tag_rng.view_tag(&data.receiver_1.encrypted_note, &receiver_pks[1]),
+			tag_rng.view_tag(&data.receiver_2.encrypted_note, &receiver_pks[0]),
+		];
+		assert!(view_tags.iter().all(Option::is_some));
+
+		// a note encrypted with another RNG is not recognised
+		let other_note = EncValueList::get()[0].clone();
+		assert_eq!(tag_rng.view_tag(&other_note, &receiver_pks[0]), None);
+
+		assert_ok!(Assets::private_transfer(
+			Origin::signed(1),
+			payload,
+			view_tags,
+			[None, None],
+			[None, None]
+		));
+		assert_eq!(Assets::view_tag(2), view_tags[0]);
+		assert_eq!(Assets::view_tag(3), view_tags[1]);
+
+		// the wallet of each receiver only decrypts the notes whose tag matches
+		let notes = ledger_notes_helper();
+		for (i, seed) in receiver_seeds.iter().enumerate() {
+			let mut wallet = Wallet::new(commit_param.clone());
+			wallet.add_key(*seed, TEST_ASSET).unwrap();
+			wallet.sync(notes.iter().map(as_ledger_note), &VNList::get());
+			assert_eq!(wallet.balance(TEST_ASSET), i as AssetBalance + 10);
+		}
+	});
+}
+
+// Payload validation tests:
+
+#[test]
+fn mint_with_non_canonical_field_element_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+
+		// an x coordinate above the modulus of the Jubjub base field
+		let mut data =
+			MintData::deserialize(precomputed_coins::TEST_MINT_10_PAYLOAD.as_ref()).unwrap();
+		data.cm = [0xffu8; 32];
+		data.cm[31] = 0x7f;
+		let mut payload = [0u8; MINT_PAYLOAD_SIZE];
+		data.serialize(payload.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+			Error::<Test>::NonCanonicalFieldElement
+		);
+	});
+}
+
+#[test]
+fn transferring_with_point_not_on_curve_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// there is no point of Jubjub with x = 1
+		let mut data =
+			PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+				.unwrap();
+		data.receiver_1.cm = [0u8; 32];
+		data.receiver_1.cm[0] = 1;
+		let mut payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::PointNotOnCurve
+		);
+	});
+}
+
+#[test]
+fn transferring_with_point_not_in_subgroup_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// the points of Jubjub with x = 3 have order 8r
+		let mut data =
+			PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+				.unwrap();
+		data.sender_1.k = [0u8; 32];
+		data.sender_1.k[0] = 3;
+		let mut payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::PointNotInSubgroup
+		);
+	});
+}
+
+#[test]
+fn transferring_with_malleated_proof_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// flag A as the point at infinity, but keep its x coordinate
+		let mut data =
+			PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+				.unwrap();
+		data.proof[47] = (data.proof[47] & 0x3f) | 0x40;
+		let mut payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::MalleatedProof
+		);
+		assert_noop!(
+			Assets::batch_private_transfer(Origin::signed(1), vec![batched(payload)]),
+			Error::<Test>::MalleatedProof
+		);
+	});
+}
+
+#[test]
+fn reclaim_with_non_canonical_point_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// the identity, with the sign flag that its encoding never sets
+		let mut data =
+			ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+		data.receiver.cm = [0u8; 32];
+		data.receiver.cm[31] = 0x80;
+		let mut payload = [0u8; RECLAIM_PAYLOAD_SIZE];
+		data.serialize(payload.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::reclaim(Origin::signed(1), payload, None, None, None),
+			Error::<Test>::NonCanonicalPoint
+		);
+	});
+}
+
+#[test]
+fn mint_with_non_canonical_note_key_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+
+		let mint_data =
+			|| MintData::deserialize(precomputed_coins::TEST_MINT_10_PAYLOAD.as_ref()).unwrap();
+
+		// the top bit is not part of an X25519 coordinate
+		let mut high_bit = mint_data();
+		high_bit.encrypted_note.ephemeral_pk[31] |= 0x80;
+		// p = 2^255 - 19 is not a field element
+		let mut modulus = mint_data();
+		modulus.encrypted_note.ephemeral_pk = [0xffu8; 32];
+		modulus.encrypted_note.ephemeral_pk[0] = 0xed;
+		modulus.encrypted_note.ephemeral_pk[31] = 0x7f;
+
+		for data in [high_bit, modulus].iter() {
+			let mut payload = [0u8; MINT_PAYLOAD_SIZE];
+			data.serialize(payload.as_mut()).unwrap();
+			assert_noop!(
+				Assets::mint_private_asset(Origin::signed(1), payload, None, None),
+				Error::<Test>::NonCanonicalFieldElement
+			);
+		}
+	});
+}
+
+#[test]
+fn transferring_with_small_order_note_key_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// the shared secret of the point 0 is 0 for every recipient key
+		let mut data =
+			PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+				.unwrap();
+		data.receiver_2.encrypted_note.ephemeral_pk = [0u8; 32];
+		let mut payload = [0u8; PRIVATE_TRANSFER_PAYLOAD_SIZE];
+		data.serialize(payload.as_mut()).unwrap();
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				payload,
+				[None, None],
+				[None, None],
+				[None, None]
+			),
+			Error::<Test>::PointNotInSubgroup
+		);
+		assert_noop!(
+			Assets::batch_private_transfer(Origin::signed(1), vec![batched(payload)]),
+			Error::<Test>::PointNotInSubgroup
+		);
+	});
+}
+
+#[test]
+fn transferring_with_non_canonical_auditor_note_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// notes with valid keys, then the same key with the top bit set
+		let data =
+			PrivateTransferData::deserialize(precomputed_coins::TEST_TRANSFER_PAYLOAD.as_ref())
+				.unwrap();
+		let auditor_note = data.receiver_1.encrypted_note.clone();
+		let mut tampered_note = data.receiver_2.encrypted_note.clone();
+		tampered_note.ephemeral_pk[31] |= 0x80;
+
+		assert_noop!(
+			Assets::private_transfer(
+				Origin::signed(1),
+				precomputed_coins::TEST_TRANSFER_PAYLOAD,
+				[None, None],
+				[Some(auditor_note), Some(tampered_note)],
+				[None, None]
+			),
+			Error::<Test>::NonCanonicalFieldElement
+		);
+	});
+}
+
+#[test]
+fn reclaim_with_small_order_auditor_note_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_test(1000);
+		mint_precomputed_coins();
+
+		// an auditor note is checked even if the asset has no auditor key
+		let data =
+			ReclaimData::deserialize(precomputed_coins::TEST_RECLAIM_PAYLOAD.as_ref()).unwrap();
+		let mut auditor_note = data.receiver.encrypted_note;
+		// p - 1 has order 2
+		auditor_note.ephemeral_pk = [0xffu8; 32];
+		auditor_note.ephemeral_pk[0] = 0xec;
+		auditor_note.ephemeral_pk[31] = 0x7f;
+
+		assert_noop!(
+			Assets::reclaim(
+				Origin::signed(1),
+				precomputed_coins::TEST_RECLAIM_PAYLOAD,
+				None,
+				Some(auditor_note),
+				None
+			),
+			Error::<Test>::PointNotInSubgroup
+		);
+	});
+}
+
 // Helper functions:
 
+fn mint_seeds_helper(size: usize) -> Vec<[u8; 32]> {
+	// follows the same seed as mint_tokens_helper
+	let mut rng = ChaCha20Rng::from_seed([3u8; 32]);
+	let mut seeds = Vec::new();
+	for _ in 0usize..size {
+		let mut sk = [0u8; 32];
+		rng.fill_bytes(&mut sk);
+		seeds.push(sk);
+	}
+	seeds
+}
+
+type OwnedLedgerNote = (
+	[u8; 32],
+	MantaEciesCiphertext,
+	Option<ViewTag>,
+	Option<EncryptedMemo>,
+);
+
+fn as_ledger_note(note: &OwnedLedgerNote) -> LedgerNote {
+	LedgerNote {
+		cm: &note.0,
+		note: &note.1,
+		view_tag: note.2,
+		memo: note.3.as_ref(),
+	}
+}
+
+fn ledger_notes_helper() -> Vec<OwnedLedgerNote> {
+	let enc_value_list = EncValueList::get();
+	let note = |cm: [u8; 32], note_index: u64| {
+		(
+			cm,
+			enc_value_list[note_index as usize].clone(),
+			ViewTags::get(note_index),
+			Memos::get(note_index),
+		)
+	};
+	let mut notes = Vec::new();
+	for record in System::events() {
+		match record.event {
+			Event::pallet_manta_pay(RawEvent::Minted(_, _, _, cm, _, note_index)) => {
+				notes.push(note(cm, note_index));
+			}
+			Event::pallet_manta_pay(RawEvent::PrivateTransferred(_, _, cms, _, note_indices)) => {
+				for (cm, note_index) in cms.iter().zip(note_indices.iter()) {
+					notes.push(note(*cm, *note_index));
+				}
+			}
+			Event::pallet_manta_pay(RawEvent::PrivateReclaimed(_, _, _, _, cm, _, note_index)) => {
+				notes.push(note(cm, note_index));
+			}
+			_ => {}
+		}
+	}
+	notes
+}
+
 fn mint_tokens_helper(size: usize) -> Vec<MantaAsset> {
 	let commit_param = CommitmentParam::deserialize(COMMIT_PARAM.data).unwrap();
 
@@ -940,7 +3662,12 @@ fn mint_tokens_helper(size: usize) -> Vec<MantaAsset> {
 		//println!("mint payload: {:?}", payload);
 
 		// mint a sender token
-		assert_ok!(Assets::mint_private_asset(Origin::signed(1), payload));
+		assert_ok!(Assets::mint_private_asset(
+			Origin::signed(1),
+			payload,
+			None,
+			None
+		));
 
 		pool += token_value;
 
@@ -992,7 +3719,23 @@ fn transfer_test_helper(iter: usize) {
 		//println!("transfer payload {:?}: {:?} ", i, payload);
 
 		// invoke the transfer event
-		assert_ok!(Assets::private_transfer(Origin::signed(1), payload));
+		assert_ok!(Assets::private_transfer(
+			Origin::signed(1),
+			payload,
+			[None, None],
+			[None, None],
+			[None, None]
+		));
+		assert_eq!(
+			last_event(),
+			Event::from(RawEvent::PrivateTransferred(
+				1,
+				[senders[i * 2].void_number, senders[i * 2 + 1].void_number],
+				[receiver_1.utxo, receiver_2.utxo],
+				[receiver_1.utxo[0], receiver_2.utxo[0]],
+				[(size + 2 * i) as u64, (size + 2 * i + 1) as u64],
+			))
+		);
 
 		// check the ciphertexts
 		let enc_value_list = EncValueList::get();
@@ -1051,7 +3794,7 @@ fn reclaim_test_helper(iter: usize) {
 	let mut pool = PoolBalance::get(TEST_ASSET);
 
 	for i in 0usize..iter {
-		let (payload, sender_1, sender_2, reclaim_value, _) = prepare_reclaim_payload(
+		let (payload, sender_1, sender_2, reclaim_value, receiver) = prepare_reclaim_payload(
 			&senders,
 			&commit_param,
 			&hash_param,
@@ -1066,7 +3809,25 @@ fn reclaim_test_helper(iter: usize) {
 		//println!("recalim payload: {:?}", payload);
 
 		// invoke the reclaim event
-		assert_ok!(Assets::reclaim(Origin::signed(1), payload));
+		assert_ok!(Assets::reclaim(
+			Origin::signed(1),
+			payload,
+			None,
+			None,
+			None
+		));
+		assert_eq!(
+			last_event(),
+			Event::from(RawEvent::PrivateReclaimed(
+				TEST_ASSET,
+				1,
+				reclaim_value,
+				[sender_1.asset.void_number, sender_2.asset.void_number],
+				receiver.utxo,
+				receiver.utxo[0],
+				(size + i) as u64,
+			))
+		);
 
 		// check the resulting status of the ledger storage
 		assert_eq!(TotalSupply::get(TEST_ASSET), 10_000_000);
@@ -1114,6 +3875,16 @@ fn prepare_private_transfer_payload(
 	.unwrap()
 }
 
+/// A transfer of a batch without view tags, auditor notes or memos.
+fn batched(payload: PrivateTransferPayload) -> BatchedTransfer {
+	BatchedTransfer {
+		payload,
+		view_tags: [None, None],
+		auditor_notes: [None, None],
+		memos: [None, None],
+	}
+}
+
 fn prepare_reclaim_payload(
 	senders: &Vec<MantaAsset>,
 	commit_param: &CommitmentParam,
@@ -1178,6 +3949,23 @@ fn initialize_test(amount: AssetBalance) {
 	assert_eq!(PoolBalance::get(TEST_ASSET), 0);
 }
 
+/// Mint the coins spent by `precomputed_coins::TEST_TRANSFER_PAYLOAD`
+/// and `precomputed_coins::TEST_RECLAIM_PAYLOAD`.
+fn mint_precomputed_coins() {
+	assert_ok!(Assets::mint_private_asset(
+		Origin::signed(1),
+		precomputed_coins::TEST_MINT_10_PAYLOAD,
+		None,
+		None
+	));
+	assert_ok!(Assets::mint_private_asset(
+		Origin::signed(1),
+		precomputed_coins::TEST_MINT_11_PAYLOAD,
+		None,
+		None
+	));
+}
+
 fn build_sender_meta_data(
 	senders: &Vec<MantaAsset>,
 	hash_param: &HashParam,
@@ -0,0 +1,155 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The proof systems that can verify private transfers and reclaims.
+//!
+//! A runtime picks one with `Config::Verifier`.
+//! `Groth16` is the proof system the payloads of `manta-api` are built for.
+
+use crate::batch_verify;
+use ark_std::vec::Vec;
+use manta_crypto::*;
+use manta_data::*;
+
+/// A proof system for the transfer and reclaim circuits.
+/// The verification keys, and their format, belong to the proof system;
+/// the ledger stores their checksums, written when it is initialized,
+/// and caches the keys prepared by `prepare_transfer_key` and `prepare_reclaim_key`.
+pub trait Verifier {
+	/// The checksum of the commitment parameters the circuits are built with,
+	/// or `None` if it cannot be computed.
+	fn commit_param_checksum() -> Option<[u8; 32]>;
+
+	/// The commitment parameters the circuits are built with,
+	/// or `None` if they cannot be loaded.
+	fn commit_param() -> Option<CommitmentParam>;
+
+	/// The checksum of the transfer verification key,
+	/// or `None` if it cannot be computed.
+	fn transfer_key_checksum() -> Option<[u8; 32]>;
+
+	/// The checksum of the reclaim verification key,
+	/// or `None` if it cannot be computed.
+	fn reclaim_key_checksum() -> Option<[u8; 32]>;
+
+	/// The transfer verification key, prepared for verification and serialized,
+	/// or `None` if it cannot be prepared.
+	fn prepare_transfer_key() -> Option<Vec<u8>>;
+
+	/// The reclaim verification key, prepared for verification and serialized,
+	/// or `None` if it cannot be prepared.
+	fn prepare_reclaim_key() -> Option<Vec<u8>>;
+
+	/// Verify the proof of a private transfer with the prepared transfer key.
+	fn verify_transfer(prepared_key: &[u8], data: &PrivateTransferData) -> bool;
+
+	/// Verify the proofs of a batch of private transfers with the prepared transfer key.
+	/// Returns `true` only if every proof is valid.
+	fn batch_verify_transfers(prepared_key: &[u8], data: &[PrivateTransferData]) -> bool;
+
+	/// Verify the proof of a reclaim with the prepared reclaim key.
+	fn verify_reclaim(prepared_key: &[u8], data: &ReclaimData) -> bool;
+
+	/// A private transfer of the coins of `precomputed_coins::TEST_MINT_10_PAYLOAD`
+	/// and `precomputed_coins::TEST_MINT_11_PAYLOAD`, proven for this proof system.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_transfer_payload() -> PrivateTransferPayload;
+
+	/// Private transfers, each with the mints of the two coins it spends, proven for this
+	/// proof system against the ledger with the coins of all the mints.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_batch() -> Vec<([MintPayload; 2], PrivateTransferPayload)>;
+
+	/// A reclaim of the coins of `precomputed_coins::TEST_MINT_10_PAYLOAD`
+	/// and `precomputed_coins::TEST_MINT_11_PAYLOAD`, proven for this proof system.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_reclaim_payload() -> ReclaimPayload;
+}
+
+/// Groth16 over BLS12-381, with the keys of `manta_crypto`.
+pub struct Groth16;
+
+impl Verifier for Groth16 {
+	fn commit_param_checksum() -> Option<[u8; 32]> {
+		COMMIT_PARAM
+			.get_checksum()
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to compute the commitment param checksum with error: {:?}", e);
+			})
+			.ok()
+	}
+
+	fn commit_param() -> Option<CommitmentParam> {
+		CommitmentParam::deserialize(COMMIT_PARAM.data)
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to deserialize the commitment param with error: {:?}", e);
+			})
+			.ok()
+	}
+
+	fn transfer_key_checksum() -> Option<[u8; 32]> {
+		TRANSFER_PK
+			.get_checksum()
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to compute the transfer key checksum with error: {:?}", e);
+			})
+			.ok()
+	}
+
+	fn reclaim_key_checksum() -> Option<[u8; 32]> {
+		RECLAIM_PK
+			.get_checksum()
+			.map_err(|e| {
+				log::error!(target: "manta-pay", "failed to compute the reclaim key checksum with error: {:?}", e);
+			})
+			.ok()
+	}
+
+	fn prepare_transfer_key() -> Option<Vec<u8>> {
+		batch_verify::prepare_key(&TRANSFER_PK)
+	}
+
+	fn prepare_reclaim_key() -> Option<Vec<u8>> {
+		batch_verify::prepare_key(&RECLAIM_PK)
+	}
+
+	fn verify_transfer(prepared_key: &[u8], data: &PrivateTransferData) -> bool {
+		batch_verify::verify_transfer(prepared_key, data)
+	}
+
+	fn batch_verify_transfers(prepared_key: &[u8], data: &[PrivateTransferData]) -> bool {
+		batch_verify::batch_verify_transfers(prepared_key, data)
+	}
+
+	fn verify_reclaim(prepared_key: &[u8], data: &ReclaimData) -> bool {
+		batch_verify::verify_reclaim(prepared_key, data)
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_transfer_payload() -> PrivateTransferPayload {
+		crate::precomputed_coins::TEST_TRANSFER_PAYLOAD
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_batch() -> Vec<([MintPayload; 2], PrivateTransferPayload)> {
+		crate::precomputed_coins::TEST_BATCH.to_vec()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_reclaim_payload() -> ReclaimPayload {
+		crate::precomputed_coins::TEST_RECLAIM_PAYLOAD
+	}
+}
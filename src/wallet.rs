@@ -0,0 +1,298 @@
+// Copyright 2019-2021 Manta Network.
+// This file is part of pallet-manta-pay.
+//
+// pallet-manta-pay is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// pallet-manta-pay is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with pallet-manta-pay.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A wallet helper that scans the encrypted notes of the ledger
+//! and recovers the private coins owned by a user, and an auditor
+//! helper that decrypts the private history of an asset.
+//!
+//! A coin is owned by a key if the note can be decrypted with the key's
+//! ECIES secret key _and_ the commitment re-derived from the key and the
+//! decrypted value matches the commitment on the ledger.
+//! If a note comes with a view tag, the tag is checked first and the
+//! decryption is skipped when it does not match.
+//! The encrypted memo stored next to a note is returned with the coin.
+//! Notes whose plaintext is not a valid note are skipped.
+#![cfg(feature = "std")]
+
+use crate::*;
+use ark_std::rand::{CryptoRng, Error as RandError, RngCore};
+use core::convert::TryInto;
+use manta_asset::*;
+
+/// Errors of the wallet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalletError {
+	/// Failed to derive the receiving key from the seed.
+	KeyDerivationFail,
+}
+
+/// A receiving key of the wallet.
+#[derive(Clone)]
+struct WalletKey {
+	/// The seed that the coins and the receiving address are sampled from.
+	seed: [u8; 32],
+	/// The ECIES secret key that the notes are encrypted to.
+	ecsk: MantaEciesSecretKey,
+}
+
+impl WalletKey {
+	/// The view tag of `note` as seen by this key.
+	fn view_tag(&self, note: &MantaEciesCiphertext) -> ViewTag {
+		let shared_secret = <MantaCrypto as Ecies>::shared_secret(&self.ecsk, note);
+		derive_view_tag(&shared_secret)
+	}
+}
+
+/// A random number generator that keeps the 32-byte draws of the wrapped `R`,
+/// so that a sender can recover the view tags of the notes it encrypts.
+/// The ephemeral secret key of an ECIES note is drawn from the RNG passed to
+/// `MantaAssetShieldedAddress::process`, and is not returned by the payload builder.
+/// Passing this wrapper instead keeps the key around, and the view tag follows from
+/// it and the ECIES public key of the receiver.
+pub struct ViewTagRng<R> {
+	rng: R,
+	draws: Vec<[u8; 32]>,
+}
+
+impl<R: RngCore + CryptoRng> ViewTagRng<R> {
+	/// Wrap `rng`.
+	pub fn new(rng: R) -> Self {
+		Self {
+			rng,
+			draws: Vec::new(),
+		}
+	}
+
+	/// The view tag of `note`, encrypted to the ECIES public key `receiver_pk`
+	/// with a key drawn from this RNG.
+	/// Returns `None` if the ephemeral key of `note` was not drawn from this RNG.
+	pub fn view_tag(&self, note: &MantaEciesCiphertext, receiver_pk: &[u8; 32]) -> Option<ViewTag> {
+		let ephemeral_sk = self.draws.iter().find(|sk| {
+			x25519_dalek::x25519(**sk, x25519_dalek::X25519_BASEPOINT_BYTES) == note.ephemeral_pk
+		})?;
+		// the X25519 exchange gives the same shared secret as the one the receiver
+		// derives, so the tag matches the one its wallet checks
+		Some(derive_view_tag(&x25519_dalek::x25519(
+			*ephemeral_sk,
+			*receiver_pk,
+		)))
+	}
+
+	fn record(&mut self, dest: &[u8]) {
+		if let Ok(draw) = dest.try_into() {
+			self.draws.push(draw);
+		}
+	}
+}
+
+impl<R: RngCore + CryptoRng> RngCore for ViewTagRng<R> {
+	fn next_u32(&mut self) -> u32 {
+		self.rng.next_u32()
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.rng.next_u64()
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		self.rng.fill_bytes(dest);
+		self.record(dest);
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+		self.rng.try_fill_bytes(dest)?;
+		self.record(dest);
+		Ok(())
+	}
+}
+
+impl<R: RngCore + CryptoRng> CryptoRng for ViewTagRng<R> {}
+
+/// An encrypted note of the ledger, together with the data stored next to it.
+#[derive(Clone, Copy)]
+pub struct LedgerNote<'a> {
+	/// The commitment of the coin.
+	pub cm: &'a [u8; 32],
+	/// The encrypted value of the coin.
+	pub note: &'a MantaEciesCiphertext,
+	/// The optional view tag of the note.
+	pub view_tag: Option<ViewTag>,
+	/// The optional encrypted memo of the note.
+	pub memo: Option<&'a EncryptedMemo>,
+}
+
+/// A coin recovered by the wallet.
+#[derive(Clone)]
+pub struct OwnedCoin {
+	/// The coin.
+	pub asset: MantaAsset,
+	/// The encrypted memo attached to the coin, if any.
+	pub memo: Option<EncryptedMemo>,
+}
+
+/// A wallet that keeps track of the unspent coins of its keys.
+pub struct Wallet {
+	commit_param: CommitmentParam,
+	keys: Vec<WalletKey>,
+	coins: Vec<OwnedCoin>,
+}
+
+impl Wallet {
+	/// Build an empty wallet.
+	pub fn new(commit_param: CommitmentParam) -> Self {
+		Self {
+			commit_param,
+			keys: Vec::new(),
+			coins: Vec::new(),
+		}
+	}
+
+	/// Add a key, derived from `seed`, that receives coins of `asset_id`.
+	pub fn add_key(&mut self, seed: [u8; 32], asset_id: AssetId) -> Result<(), WalletError> {
+		let receiver = MantaAssetFullReceiver::sample(&self.commit_param, &seed, &asset_id, &())
+			.map_err(|_| WalletError::KeyDerivationFail)?;
+		self.keys.push(WalletKey {
+			seed,
+			ecsk: receiver.spending_info.ecsk,
+		});
+		Ok(())
+	}
+
+	/// Scan a single note of the ledger.
+	/// Returns `true` if a new coin is recovered.
+	pub fn scan(&mut self, ledger_note: LedgerNote) -> bool {
+		let LedgerNote {
+			cm,
+			note,
+			view_tag,
+			memo,
+		} = ledger_note;
+		if self.coins.iter().any(|coin| coin.asset.utxo == *cm) {
+			return false;
+		}
+		for key in self.keys.iter() {
+			if let Some(view_tag) = view_tag {
+				if view_tag != key.view_tag(note) {
+					continue;
+				}
+			}
+			let plaintext = match <MantaCrypto as Ecies>::decrypt(&key.ecsk, note) {
+				Ok(plaintext) => plaintext,
+				Err(_) => continue,
+			};
+			let (asset_id, value) = match decode_note(&plaintext) {
+				Some(note) => note,
+				None => continue,
+			};
+			let asset = match MantaAsset::sample(&self.commit_param, &key.seed, &asset_id, &value) {
+				Ok(asset) => asset,
+				Err(_) => continue,
+			};
+			if asset.utxo == *cm {
+				self.coins.push(OwnedCoin {
+					asset,
+					memo: memo.cloned(),
+				});
+				return true;
+			}
+		}
+		false
+	}
+
+	/// Drop the coins whose void numbers are in `void_numbers`.
+	pub fn prune(&mut self, void_numbers: &[MantaRandomValue]) {
+		self.coins
+			.retain(|coin| !void_numbers.contains(&coin.asset.void_number));
+	}
+
+	/// Scan a stream of ledger notes and then drop the coins that have been spent.
+	pub fn sync<'a, I>(&mut self, notes: I, void_numbers: &[MantaRandomValue])
+	where
+		I: IntoIterator<Item = LedgerNote<'a>>,
+	{
+		for note in notes {
+			self.scan(note);
+		}
+		self.prune(void_numbers);
+	}
+
+	/// The unspent coins of this wallet.
+	pub fn coins(&self) -> &[OwnedCoin] {
+		&self.coins
+	}
+
+	/// The total value of the unspent coins of `asset_id`.
+	pub fn balance(&self, asset_id: AssetId) -> AssetBalance {
+		self.coins
+			.iter()
+			.filter(|coin| coin.asset.asset_id == asset_id)
+			.map(|coin| coin.asset.priv_info.value)
+			.sum()
+	}
+}
+
+/// Decode the plaintext of a note into `(asset_id, value)`.
+/// Returns `None` if the plaintext does not have the size of a note:
+/// anyone can store a note, so its plaintext cannot be trusted.
+pub(crate) fn decode_note(plaintext: &[u8]) -> Option<(AssetId, AssetBalance)> {
+	let asset_id_len = core::mem::size_of::<AssetId>();
+	if plaintext.len() != asset_id_len + core::mem::size_of::<AssetBalance>() {
+		return None;
+	}
+	let (asset_id, value) = plaintext.split_at(asset_id_len);
+	Some((
+		AssetId::from_le_bytes(asset_id.try_into().ok()?),
+		AssetBalance::from_le_bytes(value.try_into().ok()?),
+	))
+}
+
+/// An auditor that decrypts the private history of an asset with the
+/// auditor secret key registered via `set_auditor_key`.
+pub struct Auditor {
+	ecsk: MantaEciesSecretKey,
+}
+
+impl Auditor {
+	/// Build an auditor from its ECIES secret key.
+	pub fn new(ecsk: MantaEciesSecretKey) -> Self {
+		Self { ecsk }
+	}
+
+	/// Decrypt the history of `asset_id` from `auditor_notes`, i.e., the entries of
+	/// `AuditorNotes`. Returns the `(note_index, value)` of every note of this asset,
+	/// ordered by note index.
+	/// The values are the ones declared by the senders: the ledger does not check that
+	/// an auditor note matches the note of the receiver.
+	pub fn history<I>(&self, asset_id: AssetId, auditor_notes: I) -> Vec<(u64, AssetBalance)>
+	where
+		I: IntoIterator<Item = (u64, MantaEciesCiphertext)>,
+	{
+		let mut history: Vec<_> = auditor_notes
+			.into_iter()
+			.filter_map(|(note_index, note)| {
+				let plaintext = <MantaCrypto as Ecies>::decrypt(&self.ecsk, &note).ok()?;
+				let (note_asset_id, value) = decode_note(&plaintext)?;
+				if note_asset_id == asset_id {
+					Some((note_index, value))
+				} else {
+					None
+				}
+			})
+			.collect();
+		history.sort_unstable_by_key(|(note_index, _)| *note_index);
+		history
+	}
+}